@@ -0,0 +1,169 @@
+/// Offscreen render-to-texture capture of the composited frame, for recording/streaming the
+/// live set independent of the window: the frame is copied into a persistent `wgpu::Texture`
+/// (rather than read back from the swapchain texture directly) so capture keeps working across
+/// swapchain resizes and survives the surface being dropped/recreated around a suspend/resume
+/// cycle, the same way `Application::suspend`/`resume` keep everything but the surface alive.
+use std::sync::mpsc;
+use std::sync::Arc;
+
+/// One captured frame: tightly-packed (no row padding) RGBA8, ready to hand to a video encoder
+/// or to push out as a projection-output source.
+pub struct CaptureFrame {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+pub struct CaptureOutput {
+    device: Arc<wgpu::Device>,
+    sender: mpsc::Sender<CaptureFrame>,
+    capture_texture: Option<wgpu::Texture>,
+    readback_buffer: Option<wgpu::Buffer>,
+    size: (u32, u32),
+    // `copy_texture_to_buffer` requires each row to be a multiple of
+    // `COPY_BYTES_PER_ROW_ALIGNMENT` (256 bytes); this is the padded stride we allocated
+    // `readback_buffer` with, not `width * 4`.
+    padded_bytes_per_row: u32,
+}
+
+impl CaptureOutput {
+    /// Returns the manager plus the receiving end of its frame channel; the caller hangs onto
+    /// the `Receiver` (e.g. on `Application`) and hands it off to whatever consumes frames.
+    pub fn new(device: Arc<wgpu::Device>) -> (Self, mpsc::Receiver<CaptureFrame>) {
+        let (sender, receiver) = mpsc::channel();
+        (
+            CaptureOutput {
+                device,
+                sender,
+                capture_texture: None,
+                readback_buffer: None,
+                size: (0, 0),
+                padded_bytes_per_row: 0,
+            },
+            receiver,
+        )
+    }
+
+    fn ensure_resources(&mut self, width: u32, height: u32) {
+        if self.size == (width, height) && self.capture_texture.is_some() {
+            return;
+        }
+
+        let capture_texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Capture texture"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_DST
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let unpadded_bytes_per_row = width * 4;
+        let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+
+        let readback_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Capture readback buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        self.capture_texture = Some(capture_texture);
+        self.readback_buffer = Some(readback_buffer);
+        self.size = (width, height);
+        self.padded_bytes_per_row = padded_bytes_per_row;
+    }
+
+    /// Copies `source` (this frame's just-rendered, presentable texture) into the persistent
+    /// capture texture and queues a texture->buffer readback copy. Call before `present()`,
+    /// since `present()` may invalidate the source texture.
+    pub fn capture(&mut self, encoder: &mut wgpu::CommandEncoder, source: &wgpu::Texture, width: u32, height: u32) {
+        self.ensure_resources(width, height);
+        let capture_texture = self.capture_texture.as_ref().unwrap();
+        let readback_buffer = self.readback_buffer.as_ref().unwrap();
+        let extent = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        encoder.copy_texture_to_texture(
+            wgpu::ImageCopyTexture {
+                texture: source,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyTexture {
+                texture: capture_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            extent,
+        );
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: capture_texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: readback_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            extent,
+        );
+    }
+
+    /// Maps the readback buffer written by the most recent `capture()`, blocks on
+    /// `device.poll()` for the map to resolve, strips the row padding back out, and sends the
+    /// tightly-packed frame down the channel. Call after `queue.submit()` so the copy is
+    /// guaranteed to have actually run.
+    pub fn finish_frame(&mut self) {
+        let (width, height) = self.size;
+        if width == 0 || height == 0 {
+            return;
+        }
+        let readback_buffer = self.readback_buffer.as_ref().unwrap();
+        let slice = readback_buffer.slice(..);
+
+        let (map_tx, map_rx) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = map_tx.send(result);
+        });
+        // `map_async`'s callback only fires once the device has made progress; block until
+        // it resolves so the frame we hand out is this frame's, not some future one's.
+        self.device.poll(wgpu::Maintain::Wait);
+
+        if let Ok(Ok(())) = map_rx.recv() {
+            let padded = slice.get_mapped_range();
+            let unpadded_bytes_per_row = (width * 4) as usize;
+            let mut rgba = Vec::with_capacity(unpadded_bytes_per_row * height as usize);
+            for row in padded.chunks(self.padded_bytes_per_row as usize) {
+                rgba.extend_from_slice(&row[..unpadded_bytes_per_row]);
+            }
+            drop(padded);
+            readback_buffer.unmap();
+
+            // An Err here just means nothing's currently consuming frames (no encoder or
+            // projection sink attached yet); that's not a capture failure.
+            let _ = self.sender.send(CaptureFrame { width, height, rgba });
+        }
+    }
+}
@@ -0,0 +1,359 @@
+/// This module handles radiance output to a laser (ILDA/galvo) interface:
+/// reading back a node's rendered frame, tracing it into a vector scan path,
+/// and streaming that path over the network to a laser DAC,
+/// the same way `winit_output` turns a node's rendered frame into pixels on a screen.
+
+use std::collections::HashMap;
+use std::net::UdpSocket;
+
+/// A single point in a laser scan path, in normalized device coordinates ([-1, 1] on each axis,
+/// matching the existing `map` homography convention used by the projection-mapped screens).
+#[derive(Debug, Clone, Copy)]
+pub struct LaserPoint {
+    pub x: f32,
+    pub y: f32,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    // True at blanking (beam-off) points inserted between unconnected polylines,
+    // so the galvo can jump to the next shape without drawing a streak across the frame.
+    pub blanked: bool,
+}
+
+#[derive(Debug)]
+struct LaserOutput {
+    // Cached props
+    visible: bool,
+    config: LaserOutputConfig,
+
+    // Resources
+    socket: UdpSocket,
+    render_target_id: radiance::RenderTargetId,
+    render_target: radiance::RenderTarget,
+
+    // Internal
+    initial_update: bool,
+}
+
+/// Per-output laser DAC configuration, mirroring how `ScreenOutputNodeProps` carries
+/// `screen`/`map`: cheap to clone, read fresh from `props` every `update()`.
+#[derive(Debug, Clone)]
+pub struct LaserOutputConfig {
+    pub points_per_frame: usize,
+    pub max_scan_rate_pps: f32,
+    pub blanking_dwell_points: usize,
+    // Row-major 3x3 homography, reusing the same convention as the "uvmap" effect's `map`.
+    pub map: [f32; 9],
+}
+
+impl Default for LaserOutputConfig {
+    fn default() -> Self {
+        LaserOutputConfig {
+            points_per_frame: 2000,
+            max_scan_rate_pps: 30_000.,
+            blanking_dwell_points: 3,
+            map: [1., 0., 0., 0., 1., 0., 0., 0., 1.],
+        }
+    }
+}
+
+pub struct LaserOutputManager {
+    laser_outputs: HashMap<radiance::NodeId, LaserOutput>,
+}
+
+impl LaserOutputManager {
+    pub fn new() -> Self {
+        LaserOutputManager {
+            laser_outputs: HashMap::new(),
+        }
+    }
+
+    pub fn render_targets_iter(&self) -> impl Iterator<Item=(&radiance::RenderTargetId, &radiance::RenderTarget)> {
+        self.laser_outputs.values().map(|laser_output| (&laser_output.render_target_id, &laser_output.render_target))
+    }
+
+    /// Creates/prunes `LaserOutput`s to match `radiance::NodeProps::LaserOutputNode`s in `props`,
+    /// and refreshes each one's cached config, exactly as `WinitOutput::update` does for screens.
+    pub fn update(&mut self, props: &mut radiance::Props) {
+        for laser_output in self.laser_outputs.values_mut() {
+            laser_output.initial_update = true;
+        }
+
+        self.laser_outputs.retain(|id, _| props.node_props.get(id).map(|node_props| matches!(node_props, radiance::NodeProps::LaserOutputNode(_))).unwrap_or(false));
+
+        for (node_id, node_props) in props.node_props.iter() {
+            if let radiance::NodeProps::LaserOutputNode(_) = node_props {
+                if !self.laser_outputs.contains_key(node_id) {
+                    self.laser_outputs.insert(*node_id, Self::new_laser_output());
+                }
+            }
+        }
+
+        for (node_id, laser_output) in self.laser_outputs.iter_mut() {
+            let laser_output_props: &mut radiance::LaserOutputNodeProps = props.node_props.get_mut(node_id).unwrap().try_into().unwrap();
+            laser_output.visible = laser_output_props.visible;
+            laser_output.config = laser_output_props.config.clone();
+        }
+    }
+
+    fn new_laser_output() -> LaserOutput {
+        // Bind an ephemeral local port; the DAC address is supplied per-frame by the caller,
+        // since the laser interface's IP is configured on the node, not fixed at startup.
+        let socket = UdpSocket::bind("0.0.0.0:0").expect("failed to bind laser output socket");
+
+        let render_target_id = radiance::RenderTargetId::gen();
+        let render_target: radiance::RenderTarget = serde_json::from_value(serde_json::json!({
+            "width": 1920,
+            "height": 1080,
+            "dt": 1. / 60.
+        })).unwrap();
+
+        LaserOutput {
+            visible: false,
+            config: LaserOutputConfig::default(),
+            socket,
+            render_target_id,
+            render_target,
+            initial_update: false,
+        }
+    }
+
+    /// Takes `rgba` (tightly-packed, 4 bytes/pixel, `width` x `height`) for the node's
+    /// render target, traces it into a laser frame, and streams it to `dac_addr` over UDP.
+    pub fn send_frame(&self, node_id: &radiance::NodeId, dac_addr: &str, rgba: &[u8], width: u32, height: u32) -> std::io::Result<()> {
+        let laser_output = match self.laser_outputs.get(node_id) {
+            Some(laser_output) if laser_output.initial_update && laser_output.visible => laser_output,
+            _ => return Ok(()),
+        };
+
+        let points = build_laser_frame(rgba, width, height, &laser_output.config);
+        send_laser_frame(&laser_output.socket, dac_addr, &points)
+    }
+}
+
+/// Turns a rendered frame into an ordered, blanked, fixed-rate laser scan path.
+fn build_laser_frame(rgba: &[u8], width: u32, height: u32, config: &LaserOutputConfig) -> Vec<LaserPoint> {
+    const LUMINANCE_THRESHOLD: f32 = 0.35;
+
+    let polylines = trace_contours(rgba, width, height, LUMINANCE_THRESHOLD);
+    let ordered = order_polylines_nearest_neighbor(polylines);
+    let path = resample_path(&ordered, config.points_per_frame);
+
+    attach_color_and_blank(&path, rgba, width, height, config.blanking_dwell_points, &config.map)
+}
+
+/// Traces the bright regions of `rgba` into polylines of normalized ([-1, 1]) image-space
+/// coordinates, using Moore-neighbor boundary tracing on the thresholded luminance image.
+/// This is the same kind of result marching squares produces (one closed polyline per
+/// connected bright region's outer boundary) but is simpler to implement directly over a
+/// binary mask, which is all we need here since we threshold by luminance first anyway.
+fn trace_contours(rgba: &[u8], width: u32, height: u32, threshold: f32) -> Vec<Vec<(f32, f32)>> {
+    let (w, h) = (width as usize, height as usize);
+    if w == 0 || h == 0 {
+        return Vec::new();
+    }
+
+    let luminance = |x: usize, y: usize| -> f32 {
+        let i = (y * w + x) * 4;
+        let (r, g, b) = (rgba[i] as f32, rgba[i + 1] as f32, rgba[i + 2] as f32);
+        (0.2126 * r + 0.7152 * g + 0.0722 * b) / 255.
+    };
+    let is_bright = |x: i64, y: i64| -> bool {
+        x >= 0 && y >= 0 && (x as usize) < w && (y as usize) < h && luminance(x as usize, y as usize) >= threshold
+    };
+
+    // 8-connected neighbor offsets, in clockwise order starting "north".
+    const NEIGHBORS: [(i64, i64); 8] = [(0, -1), (1, -1), (1, 0), (1, 1), (0, 1), (-1, 1), (-1, 0), (-1, -1)];
+
+    let mut visited_starts = vec![false; w * h];
+    let mut contours = Vec::new();
+
+    for y0 in 0..h {
+        for x0 in 0..w {
+            let idx = y0 * w + x0;
+            if visited_starts[idx] || !is_bright(x0 as i64, y0 as i64) {
+                continue;
+            }
+            // Only start tracing from a boundary pixel (bright with a non-bright west neighbor),
+            // so each connected region contributes one contour instead of one per interior pixel.
+            if is_bright(x0 as i64 - 1, y0 as i64) {
+                continue;
+            }
+
+            let mut contour = Vec::new();
+            let (start_x, start_y) = (x0 as i64, y0 as i64);
+            let (mut x, mut y) = (start_x, start_y);
+            let mut entry_dir = 6; // arrived from the west, as if stepping in from outside
+
+            loop {
+                visited_starts[(y as usize) * w + (x as usize)] = true;
+                contour.push((x as f32 / w as f32 * 2. - 1., y as f32 / h as f32 * 2. - 1.));
+
+                let mut found = None;
+                for i in 0..8 {
+                    let dir = (entry_dir + 1 + i) % 8;
+                    let (dx, dy) = NEIGHBORS[dir];
+                    if is_bright(x + dx, y + dy) {
+                        found = Some((dir, dx, dy));
+                        break;
+                    }
+                }
+
+                match found {
+                    Some((dir, dx, dy)) => {
+                        x += dx;
+                        y += dy;
+                        // Re-enter the next search from the opposite of the direction we came from.
+                        entry_dir = (dir + 4) % 8;
+                    },
+                    None => break, // isolated pixel; contour is just this one point
+                }
+
+                if (x, y) == (start_x, start_y) || contour.len() > w * h {
+                    break;
+                }
+            }
+
+            contours.push(contour);
+        }
+    }
+
+    contours
+}
+
+/// Greedily orders polylines by nearest-neighbor travel distance between an endpoint of one
+/// and the start of the next, to minimize the total galvo jump distance across the frame.
+fn order_polylines_nearest_neighbor(mut polylines: Vec<Vec<(f32, f32)>>) -> Vec<Vec<(f32, f32)>> {
+    let mut ordered = Vec::with_capacity(polylines.len());
+    let mut cursor = (0., 0.);
+
+    while !polylines.is_empty() {
+        let (best_idx, reverse) = polylines.iter().enumerate()
+            .map(|(i, line)| {
+                let start = *line.first().unwrap();
+                let end = *line.last().unwrap();
+                let d_start = dist(cursor, start);
+                let d_end = dist(cursor, end);
+                if d_end < d_start { (i, true, d_end) } else { (i, false, d_start) }
+            })
+            .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap())
+            .map(|(i, reverse, _)| (i, reverse))
+            .unwrap();
+
+        let mut line = polylines.remove(best_idx);
+        if reverse {
+            line.reverse();
+        }
+        cursor = *line.last().unwrap();
+        ordered.push(line);
+    }
+
+    ordered
+}
+
+fn dist(a: (f32, f32), b: (f32, f32)) -> f32 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
+
+/// Resamples a sequence of polylines to exactly `n_points` total, evenly spaced by arc length,
+/// so the DAC always receives a fixed point rate regardless of scene complexity.
+fn resample_path(polylines: &[Vec<(f32, f32)>], n_points: usize) -> Vec<(f32, f32)> {
+    let total_length: f32 = polylines.iter().map(|line| {
+        line.windows(2).map(|w| dist(w[0], w[1])).sum::<f32>()
+    }).sum();
+
+    if total_length <= 0. || n_points == 0 {
+        return Vec::new();
+    }
+
+    let step = total_length / n_points as f32;
+    let mut out = Vec::with_capacity(n_points);
+    let mut carry = 0.;
+
+    for line in polylines {
+        for w in line.windows(2) {
+            let (a, b) = (w[0], w[1]);
+            let seg_len = dist(a, b);
+            if seg_len <= 0. {
+                continue;
+            }
+            let mut d = carry;
+            while d < seg_len {
+                let t = d / seg_len;
+                out.push((a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t));
+                d += step;
+            }
+            carry = d - seg_len;
+        }
+    }
+
+    // Float stepping can land one point over or under; pin the count to `n_points`.
+    let last = *out.last().unwrap();
+    out.resize(n_points, last);
+    out
+}
+
+/// Applies the homography, samples source color at each point, and inserts blanked points
+/// between path jumps so a real galvo has time to settle before the beam turns back on.
+/// Sharp corners within a path get no extra anchor points; only jumps are padded.
+fn attach_color_and_blank(path: &[(f32, f32)], rgba: &[u8], width: u32, height: u32, blanking_dwell_points: usize, map: &[f32; 9]) -> Vec<LaserPoint> {
+    let (w, h) = (width as usize, height as usize);
+    let sample = |p: (f32, f32)| -> (u8, u8, u8) {
+        if w == 0 || h == 0 {
+            return (0, 0, 0);
+        }
+        let x = (((p.0 + 1.) / 2. * w as f32) as usize).min(w - 1);
+        let y = (((p.1 + 1.) / 2. * h as f32) as usize).min(h - 1);
+        let i = (y * w + x) * 4;
+        (rgba[i], rgba[i + 1], rgba[i + 2])
+    };
+
+    const JUMP_THRESHOLD: f32 = 0.1; // normalized units; a gap bigger than this needs blanking
+
+    let mut out = Vec::with_capacity(path.len() + blanking_dwell_points * path.len() / 8);
+    for (i, &p) in path.iter().enumerate() {
+        let mapped = apply_homography(map, p);
+        let (r, g, b) = sample(p);
+
+        if i > 0 && dist(path[i - 1], p) > JUMP_THRESHOLD {
+            // Dwell at the jump target with the beam off before resuming drawing,
+            // so the galvo has settled by the time the beam turns back on.
+            for _ in 0..blanking_dwell_points {
+                out.push(LaserPoint { x: mapped.0, y: mapped.1, r: 0, g: 0, b: 0, blanked: true });
+            }
+        }
+
+        out.push(LaserPoint { x: mapped.0, y: mapped.1, r, g, b, blanked: false });
+    }
+
+    out
+}
+
+/// Applies a row-major 3x3 homography (the same convention as the "uvmap" effect's `map`)
+/// to a normalized point.
+fn apply_homography(map: &[f32; 9], p: (f32, f32)) -> (f32, f32) {
+    let (x, y) = (p.0, p.1);
+    let xp = map[0] * x + map[1] * y + map[2];
+    let yp = map[3] * x + map[4] * y + map[5];
+    let wp = map[6] * x + map[7] * y + map[8];
+    if wp != 0. { (xp / wp, yp / wp) } else { (xp, yp) }
+}
+
+/// Serializes `points` into a simple UDP laser-frame protocol: a 4-byte point count header
+/// (little-endian u32) followed by 12 bytes per point (x, y as little-endian f32, r, g, b, and
+/// a blanked flag byte), and sends it in one packet. Real ILDA/galvo interfaces vary in their
+/// exact wire format; this keeps the shape `LaserPoint` already carries, one point per 12 bytes.
+fn send_laser_frame(socket: &UdpSocket, dac_addr: &str, points: &[LaserPoint]) -> std::io::Result<()> {
+    let mut buf = Vec::with_capacity(4 + points.len() * 12);
+    buf.extend_from_slice(&(points.len() as u32).to_le_bytes());
+    for point in points {
+        buf.extend_from_slice(&point.x.to_le_bytes());
+        buf.extend_from_slice(&point.y.to_le_bytes());
+        buf.push(point.r);
+        buf.push(point.g);
+        buf.push(point.b);
+        buf.push(point.blanked as u8);
+    }
+    socket.send_to(&buf, dac_addr)?;
+    Ok(())
+}
@@ -10,8 +10,8 @@ use egui_wgpu::renderer::{Renderer, ScreenDescriptor};
 use egui_winit::winit;
 use egui_winit::winit::{
     event::*,
-    event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    event_loop::{ControlFlow, EventLoopBuilder, EventLoopWindowTarget},
+    window::{Fullscreen, Window, WindowBuilder},
 };
 use serde_json::json;
 use std::collections::HashMap;
@@ -24,329 +24,712 @@ use radiance::{
 };
 
 mod ui;
-use ui::{mosaic, modal, modal_shown};
+use ui::{mosaic_ui, modal, modal_shown};
 use ui::{SpectrumWidget, WaveformWidget};
 
 mod winit_output;
 use winit_output::WinitOutput;
 
+mod laser_output;
+use laser_output::LaserOutputManager;
+
+mod capture_output;
+use capture_output::{CaptureFrame, CaptureOutput};
+
+mod preview_mips;
+use preview_mips::PreviewMipGenerator;
+
+// Headless counterpart to `winit_output`, for batch-rendering `OffscreenOutputNode`s from a
+// CLI tool; not driven by this windowed `run()` loop.
+mod offscreen_output;
+
 const BACKGROUND_COLOR: egui::Color32 = egui::Color32::from_rgb(51, 51, 51);
 
+/// A VJ-facing latency/tearing tradeoff, mapped onto whatever present modes the adapter
+/// actually supports rather than assuming any particular one is present.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VsyncMode {
+    /// Prefer tearing over any frame latency: `Immediate`, falling back toward `Fifo`.
+    Off,
+    /// Always wait for vblank: `Fifo`, which every adapter is required to support.
+    On,
+    /// Low-latency without tearing where possible: `Mailbox`, falling back to `Immediate`
+    /// and finally `Fifo`.
+    LowLatency,
+}
+
+impl VsyncMode {
+    /// Picks the best present mode this preference can get on `supported`, walking a
+    /// fallback chain instead of assuming the adapter supports any particular mode.
+    fn best_present_mode(self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+        let chain: &[wgpu::PresentMode] = match self {
+            VsyncMode::Off => &[
+                wgpu::PresentMode::Immediate,
+                wgpu::PresentMode::Mailbox,
+                wgpu::PresentMode::Fifo,
+            ],
+            VsyncMode::On => &[wgpu::PresentMode::Fifo],
+            VsyncMode::LowLatency => &[
+                wgpu::PresentMode::Mailbox,
+                wgpu::PresentMode::Immediate,
+                wgpu::PresentMode::Fifo,
+            ],
+        };
+        chain
+            .iter()
+            .copied()
+            .find(|mode| supported.contains(mode))
+            // Fifo is required by the wgpu spec to always be supported.
+            .unwrap_or(wgpu::PresentMode::Fifo)
+    }
+}
+
+/// Carries timing/parameter updates from background threads (a MIDI listener, an OSC
+/// server, an external beat clock) into the render loop via an `EventLoopProxy`, so
+/// those threads can drive the graph without touching `Props` or blocking a frame.
+#[derive(Debug, Clone)]
+pub enum RadianceEvent {
+    /// An external clock (e.g. a DJ mixer's clock output) ticked a beat; handled by
+    /// requesting an immediate redraw. `Mir`'s tempo is not adjusted from it.
+    BeatTick,
+    MidiControlChange { cc: u8, value: u8 },
+    OscMessage { addr: String, args: Vec<f32> },
+    /// A shader file changed on disk; mirrors `ShaderWatcher`'s notifications, but
+    /// routed through here for sources (e.g. a remote editor) that aren't local files.
+    ReloadEffect(String),
+}
+
 pub fn resize(
     new_size: winit::dpi::PhysicalSize<u32>,
     config: &mut wgpu::SurfaceConfiguration,
     device: &wgpu::Device,
-    surface: &mut wgpu::Surface,
+    surface: Option<&mut wgpu::Surface>,
     screen_descriptor: Option<&mut ScreenDescriptor>,
 ) {
     if new_size.width > 0 && new_size.height > 0 {
         config.width = new_size.width;
         config.height = new_size.height;
-        surface.configure(device, config);
+        // On Android (and momentarily during a suspend/resume cycle on other platforms),
+        // there may be no surface to configure yet; just remember the new size for
+        // whenever Event::Resumed creates one.
+        if let Some(surface) = surface {
+            surface.configure(device, config);
+        }
         if let Some(screen_descriptor) = screen_descriptor {
             screen_descriptor.size_in_pixels = [config.width, config.height]
         }
     }
 }
 
-pub async fn run() {
-    env_logger::init();
-    let event_loop = EventLoop::new();
-
-    let window = WindowBuilder::new().build(&event_loop).unwrap();
-    window.set_title("Radiance");
-    window.set_maximized(true);
-
-    let size = window.inner_size();
-
-    // The instance is a handle to our GPU
-    // Backends::all => Vulkan + Metal + DX12 + Browser WebGPU
-    let instance = Arc::new(wgpu::Instance::new(wgpu::Backends::all()));
-    let mut surface = unsafe { instance.create_surface(&window) };
-    let adapter = Arc::new(
-        instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::default(),
-                compatible_surface: Some(&surface),
-                force_fallback_adapter: false,
-            })
-            .await
-            .unwrap(),
-    );
-
-    let (device, queue) = adapter
-        .request_device(
-            &wgpu::DeviceDescriptor {
-                features: wgpu::Features::TEXTURE_BINDING_ARRAY,
-                // WebGL doesn't support all of wgpu's features, so if
-                // we're building for the web we'll have to disable some.
-                limits: if cfg!(target_arch = "wasm32") {
-                    wgpu::Limits::downlevel_webgl2_defaults()
-                } else {
-                    wgpu::Limits::default()
-                },
-                label: None,
-            },
-            None, // Trace path
-        )
-        .await
-        .unwrap();
+/// Owns everything `run()`'s event loop closure used to hold as captured locals:
+/// the window, the wgpu device/surface, the egui integration, and all of radiance's
+/// own state (`Context`, `Mir`, the node graph `Props`). Pulling these into a struct
+/// (rather than a closure's capture list) is what makes `surface` droppable and
+/// re-creatable across `Event::Suspended`/`Event::Resumed` without the rest of the
+/// state going with it.
+struct Application {
+    window: Window,
+    instance: Arc<wgpu::Instance>,
+    adapter: Arc<wgpu::Adapter>,
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    // `None` whenever there's no native window to present to, i.e. before the first
+    // `Event::Resumed` on Android, or between a `Suspended`/`Resumed` pair.
+    surface: Option<wgpu::Surface>,
+    config: wgpu::SurfaceConfiguration,
+    vsync_mode: VsyncMode,
 
-    let device = Arc::new(device);
-    let queue = Arc::new(queue);
-
-    let mut winit_output = WinitOutput::new(
-        instance.clone(),
-        adapter.clone(),
-        device.clone(),
-        queue.clone(),
-    );
-
-    let mut config = wgpu::SurfaceConfiguration {
-        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-        format: surface.get_supported_formats(&adapter)[0],
-        width: size.width,
-        height: size.height,
-        present_mode: wgpu::PresentMode::Fifo,
-        alpha_mode: wgpu::CompositeAlphaMode::Auto,
-    };
-
-    // EGUI
-    let pixels_per_point = window.scale_factor() as f32;
-
-    let mut screen_descriptor = ScreenDescriptor {
-        size_in_pixels: [0, 0],
-        pixels_per_point: window.scale_factor() as f32,
-    };
-
-    resize(
-        size,
-        &mut config,
-        &device,
-        &mut surface,
-        Some(&mut screen_descriptor),
-    );
-
-    // Make a egui context:
-    let egui_ctx = egui::Context::default();
-
-    // We use the egui_winit_platform crate as the platform.
-    let mut platform = egui_winit::State::new(&event_loop);
-    platform.set_pixels_per_point(pixels_per_point);
-
-    // We use the egui_wgpu_backend crate as the render backend.
-    let mut egui_renderer = Renderer::new(&device, config.format, None, 1);
-
-    // RADIANCE, WOO
-
-    // Make a Mir
-    let mut mir = Mir::new();
-
-    // Make context
-    let mut ctx = Context::new(device.clone(), queue.clone());
-
-    // Make widgets
-    let mut waveform_widget = WaveformWidget::new(device.clone(), queue.clone(), pixels_per_point);
-    let mut spectrum_widget = SpectrumWidget::new(device.clone(), queue.clone(), pixels_per_point);
-
-    // Make an AutoDJ
-    let mut auto_dj: Option<AutoDJ> = None;
-
-    // Make a graph
-    let node1_id: NodeId = serde_json::from_value(json!("node_TW+qCFNoz81wTMca9jRIBg")).unwrap();
-    let node2_id: NodeId = serde_json::from_value(json!("node_IjPuN2HID3ydxcd4qOsCuQ")).unwrap();
-    let node3_id: NodeId = serde_json::from_value(json!("node_mW00lTCmDH/03tGyNv3iCQ")).unwrap();
-    let node4_id: NodeId = serde_json::from_value(json!("node_EdpVLI4KG5JEBRNSgKUzsw")).unwrap();
-    let node5_id: NodeId = serde_json::from_value(json!("node_I6AAXBaZKvSUfArs2vBr4A")).unwrap();
-    let node6_id: NodeId = serde_json::from_value(json!("node_I6AAXBaZKvSUfAxs2vBr4A")).unwrap();
-    let output_node_id: NodeId =
-        serde_json::from_value(json!("node_KSvPLGkiJDT+3FvPLf9JYQ")).unwrap();
-    let mut props: Props = serde_json::from_value(json!({
-        "graph": {
-            "nodes": [
-                node1_id,
-                node2_id,
-                node3_id,
-                node4_id,
-                node5_id,
-                node6_id,
-                output_node_id,
-            ],
-            "edges": [
-                {
-                    "from": node1_id,
-                    "to": node2_id,
-                    "input": 0,
-                },
-                {
-                    "from": node2_id,
-                    "to": node5_id,
-                    "input": 1,
-                },
-                {
-                    "from": node3_id,
-                    "to": node4_id,
-                    "input": 0,
-                },
-                {
-                    "from": node4_id,
-                    "to": node5_id,
-                    "input": 0,
-                },
-                {
-                    "from": node5_id,
-                    "to": output_node_id,
-                    "input": 0,
-                },
-                {
-                    "from": node6_id,
-                    "to": node1_id,
-                    "input": 0,
+    // Cloned out to worker threads (MIDI/OSC/beat clock) so they can post
+    // `RadianceEvent`s into the render loop; kept here too so `Application` itself
+    // can hand out further clones without the caller needing to plumb one through.
+    event_proxy: winit::event_loop::EventLoopProxy<RadianceEvent>,
+
+    platform: egui_winit::State,
+    egui_ctx: egui::Context,
+    egui_renderer: Renderer,
+    screen_descriptor: ScreenDescriptor,
+
+    winit_output: WinitOutput,
+    laser_output: LaserOutputManager,
+
+    // A second, borderless/fullscreen window that shows only the live set's composited output
+    // (no node editor), for the common two-display VJ setup: operator screen + projector/beam.
+    projector_window: Window,
+    projector_surface: wgpu::Surface,
+    projector_config: wgpu::SurfaceConfiguration,
+    projector_bind_group_layout: wgpu::BindGroupLayout,
+    projector_pipeline: wgpu::RenderPipeline,
+    // Which node's rendered result is shown on the projector window; the preset graph's
+    // output node, for now.
+    projector_node_id: NodeId,
+
+    capture_output: CaptureOutput,
+    capture_frames: std::sync::mpsc::Receiver<CaptureFrame>,
+    capture_enabled: bool,
+
+    mir: Mir,
+    ctx: Context,
+    waveform_widget: WaveformWidget,
+    spectrum_widget: SpectrumWidget,
+    auto_dj: Option<AutoDJ>,
+    auto_dj_enabled: bool,
+
+    props: Props,
+    preview_render_target_id: RenderTargetId,
+    render_target_list: HashMap<RenderTargetId, RenderTarget>,
+
+    node_add_textedit: String,
+    left_panel_expanded: bool,
+    node_add_wants_focus: bool,
+    insertion_point: InsertionPoint,
+
+    waveform_texture: Option<egui::TextureId>,
+    spectrum_texture: Option<egui::TextureId>,
+    // Keyed by node id so a live node's preview texture is updated in place rather than
+    // freed and re-registered with egui every frame; pruned of removed nodes in `run()`.
+    preview_textures: HashMap<NodeId, egui::TextureId>,
+    // Gives each node's preview thumbnail a mip chain to downsample from, since the render
+    // target `ctx.paint()` hands back has only ever had a single mip level.
+    preview_mips: PreviewMipGenerator,
+}
+
+impl Application {
+    async fn new(event_loop: &EventLoopWindowTarget<RadianceEvent>, event_proxy: winit::event_loop::EventLoopProxy<RadianceEvent>) -> Self {
+        let window = WindowBuilder::new().build(event_loop).unwrap();
+        window.set_title("Radiance");
+        window.set_maximized(true);
+
+        let size = window.inner_size();
+
+        // The instance is a handle to our GPU
+        // Backends::all => Vulkan + Metal + DX12 + Browser WebGPU
+        let instance = Arc::new(wgpu::Instance::new(wgpu::Backends::all()));
+        // Wrapped in an Option so it can be dropped on Event::Suspended and recreated on
+        // Event::Resumed: on Android the native window (and so the surface) doesn't survive
+        // the app being backgrounded, while everything else here (Context, Mir, graph props,
+        // egui state) does and must keep running across the cycle.
+        let surface = Some(unsafe { instance.create_surface(&window) });
+        let adapter = Arc::new(
+            instance
+                .request_adapter(&wgpu::RequestAdapterOptions {
+                    power_preference: wgpu::PowerPreference::default(),
+                    compatible_surface: surface.as_ref(),
+                    force_fallback_adapter: false,
+                })
+                .await
+                .unwrap(),
+        );
+
+        let (device, queue) = adapter
+            .request_device(
+                &wgpu::DeviceDescriptor {
+                    features: wgpu::Features::TEXTURE_BINDING_ARRAY,
+                    // WebGL doesn't support all of wgpu's features, so if
+                    // we're building for the web we'll have to disable some.
+                    limits: if cfg!(target_arch = "wasm32") {
+                        wgpu::Limits::downlevel_webgl2_defaults()
+                    } else {
+                        wgpu::Limits::default()
+                    },
+                    label: None,
                 },
-            ],
-        },
-        "node_props": {
-            node1_id.to_string(): {
-                "type": "EffectNode",
-                "name": "purple",
-                "input_count": 1,
-                "intensity": 1.0,
-            },
-            node2_id.to_string(): {
-                "type": "EffectNode",
-                "name": "droste",
-                "input_count": 1,
-                "intensity": 1.0,
-            },
-            node3_id.to_string(): {
-                "type": "EffectNode",
-                "name": "wwave",
-                "input_count": 1,
-                "intensity": 0.6,
-                "frequency": 0.25,
-            },
-            node4_id.to_string(): {
-                "type": "EffectNode",
-                "name": "zoomin",
-                "input_count": 1,
-                "intensity": 0.3,
-                "frequency": 1.0
+                None, // Trace path
+            )
+            .await
+            .unwrap();
+
+        let device = Arc::new(device);
+        let queue = Arc::new(queue);
+
+        let winit_output = WinitOutput::new(
+            instance.clone(),
+            adapter.clone(),
+            device.clone(),
+            queue.clone(),
+        );
+
+        // Manages LaserOutputNodes the same way winit_output manages ScreenOutputNodes,
+        // tracing each one's rendered frame into a scan path streamed to a laser DAC over UDP.
+        let laser_output = LaserOutputManager::new();
+
+        // Off by default; a VJ opts in from the UI since it costs a readback round-trip
+        // every frame it's enabled.
+        let (capture_output, capture_frames) = CaptureOutput::new(device.clone());
+        let capture_enabled = false;
+
+        // Defaults to `On` (Fifo) to match this app's previous hardcoded behavior;
+        // the egui UI lets a VJ trade that for lower latency at the cost of tearing.
+        let vsync_mode = VsyncMode::On;
+        let supported_present_modes =
+            surface.as_ref().unwrap().get_supported_present_modes(&adapter);
+        let mut config = wgpu::SurfaceConfiguration {
+            // COPY_SRC in addition to RENDER_ATTACHMENT so `capture_output` can copy the
+            // presentable texture out before `present()` without a second render pass.
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            format: surface.as_ref().unwrap().get_supported_formats(&adapter)[0],
+            width: size.width,
+            height: size.height,
+            present_mode: vsync_mode.best_present_mode(&supported_present_modes),
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+        };
+
+        // EGUI
+        let pixels_per_point = window.scale_factor() as f32;
+
+        let mut screen_descriptor = ScreenDescriptor {
+            size_in_pixels: [0, 0],
+            pixels_per_point: window.scale_factor() as f32,
+        };
+
+        let mut surface = surface;
+        resize(
+            size,
+            &mut config,
+            &device,
+            surface.as_mut(),
+            Some(&mut screen_descriptor),
+        );
+
+        // Make a egui context:
+        let egui_ctx = egui::Context::default();
+
+        // We use the egui_winit_platform crate as the platform.
+        let mut platform = egui_winit::State::new(event_loop);
+        platform.set_pixels_per_point(pixels_per_point);
+
+        // We use the egui_wgpu_backend crate as the render backend.
+        let egui_renderer = Renderer::new(&device, config.format, None, 1);
+
+        // RADIANCE, WOO
+
+        // Make a Mir
+        let mir = Mir::new();
+
+        // Make context
+        let ctx = Context::new(device.clone(), queue.clone());
+
+        // Make widgets
+        let waveform_widget = WaveformWidget::new(device.clone(), queue.clone(), pixels_per_point);
+        let spectrum_widget = SpectrumWidget::new(device.clone(), queue.clone(), pixels_per_point);
+
+        let preview_mips = PreviewMipGenerator::new(device.clone(), queue.clone());
+
+        // Make a graph
+        let node1_id: NodeId = serde_json::from_value(json!("node_TW+qCFNoz81wTMca9jRIBg")).unwrap();
+        let node2_id: NodeId = serde_json::from_value(json!("node_IjPuN2HID3ydxcd4qOsCuQ")).unwrap();
+        let node3_id: NodeId = serde_json::from_value(json!("node_mW00lTCmDH/03tGyNv3iCQ")).unwrap();
+        let node4_id: NodeId = serde_json::from_value(json!("node_EdpVLI4KG5JEBRNSgKUzsw")).unwrap();
+        let node5_id: NodeId = serde_json::from_value(json!("node_I6AAXBaZKvSUfArs2vBr4A")).unwrap();
+        let node6_id: NodeId = serde_json::from_value(json!("node_I6AAXBaZKvSUfAxs2vBr4A")).unwrap();
+        let output_node_id: NodeId =
+            serde_json::from_value(json!("node_KSvPLGkiJDT+3FvPLf9JYQ")).unwrap();
+
+        // Dedicated fullscreen output window: prefer a second monitor if one is plugged in
+        // (the projector/beam), falling back to the primary so this still works on a single
+        // display during development.
+        let projector_monitor = event_loop
+            .available_monitors()
+            .nth(1)
+            .or_else(|| event_loop.primary_monitor());
+        let projector_window = WindowBuilder::new()
+            .with_title("Radiance Output")
+            .with_decorations(false)
+            .with_fullscreen(Some(Fullscreen::Borderless(projector_monitor)))
+            .build(event_loop)
+            .unwrap();
+        let projector_size = projector_window.inner_size();
+        let projector_surface = unsafe { instance.create_surface(&projector_window) };
+        let projector_format = projector_surface.get_supported_formats(&adapter)[0];
+        let projector_config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: projector_format,
+            width: projector_size.width,
+            height: projector_size.height,
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+        };
+        projector_surface.configure(&device, &projector_config);
+
+        let projector_shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Projector output shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("winit_output/output.wgsl").into()),
+        });
+        let projector_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+                label: Some("projector output texture bind group layout"),
+            });
+        let projector_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Projector Output Render Pipeline Layout"),
+                bind_group_layouts: &[&projector_bind_group_layout],
+                push_constant_ranges: &[],
+            });
+        let projector_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Projector Output Render Pipeline"),
+            layout: Some(&projector_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &projector_shader_module,
+                entry_point: "vs_main",
+                buffers: &[],
             },
-            node5_id.to_string(): {
-                "type": "EffectNode",
-                "name": "uvmap",
-                "input_count": 2,
-                "intensity": 0.2,
-                "frequency": 0.0
+            fragment: Some(wgpu::FragmentState {
+                module: &projector_shader_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: projector_config.format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: Some(wgpu::Face::Back),
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
             },
-            node6_id.to_string(): {
-                "type": "ImageNode",
-                "name": "nyancat.gif",
-                "intensity": 1.0,
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
             },
-            output_node_id.to_string(): {
-                "type": "ProjectionMappedOutputNode",
-                "resolution": [1000, 1000],
-                "screens": [
+            multiview: None,
+        });
+
+        let props: Props = serde_json::from_value(json!({
+            "graph": {
+                "nodes": [
+                    node1_id,
+                    node2_id,
+                    node3_id,
+                    node4_id,
+                    node5_id,
+                    node6_id,
+                    output_node_id,
+                ],
+                "edges": [
+                    {
+                        "from": node1_id,
+                        "to": node2_id,
+                        "input": 0,
+                    },
+                    {
+                        "from": node2_id,
+                        "to": node5_id,
+                        "input": 1,
+                    },
+                    {
+                        "from": node3_id,
+                        "to": node4_id,
+                        "input": 0,
+                    },
+                    {
+                        "from": node4_id,
+                        "to": node5_id,
+                        "input": 0,
+                    },
                     {
-                        "name": "fake1",
-                        "resolution": [1920, 1080],
-                        "crop": [[0.2,0.8], [0.8,0.8], [0.8, 0.3], [0.5, 0.2], [0.2, 0.5]],
-                        "map": [1, 0.2, 0, -0.2, 1, 0, 0, 0, 1],
+                        "from": node5_id,
+                        "to": output_node_id,
+                        "input": 0,
                     },
                     {
-                        "name": "fake2",
-                        "resolution": [1920, 1080],
-                        "crop": [[0.2,0.8], [0.8,0.8], [0.8, 0.3], [0.5, 0.2], [0.2, 0.5]],
-                        "map": [1, 0.2, 0, -0.2, 1, 0, 0, 0, 1],
+                        "from": node6_id,
+                        "to": node1_id,
+                        "input": 0,
                     },
                 ],
-            }
-        },
-        "time": 0.,
-        "dt": 0.03,
-    }))
-    .unwrap();
-
-    println!("Props: {}", serde_json::to_string(&props).unwrap());
-
-    // Make render targets
-    let preview_render_target_id: RenderTargetId =
-        serde_json::from_value(json!("rt_LVrjzxhXrGU7SqFo+85zkw")).unwrap();
-    let render_target_list: HashMap<RenderTargetId, RenderTarget> = serde_json::from_value(json!({
-        preview_render_target_id.to_string(): {
-            "width": 256,
-            "height": 256,
-            "dt": 1. / 60.
-        },
-    }))
-    .unwrap();
-
-    println!(
-        "Render target list: {}",
-        serde_json::to_string(&render_target_list).unwrap()
-    );
-
-    // UI state
-    let mut node_add_textedit = String::new(); // TODO: factor this into its own component in ui/
-    let mut left_panel_expanded = false;
-    let mut node_add_wants_focus = false;
-    let mut insertion_point: InsertionPoint = Default::default();
-    let mut auto_dj_enabled = false;
-
-    let mut waveform_texture: Option<egui::TextureId> = None;
-    let mut spectrum_texture: Option<egui::TextureId> = None;
+            },
+            "node_props": {
+                node1_id.to_string(): {
+                    "type": "EffectNode",
+                    "name": "purple",
+                    "input_count": 1,
+                    "intensity": 1.0,
+                },
+                node2_id.to_string(): {
+                    "type": "EffectNode",
+                    "name": "droste",
+                    "input_count": 1,
+                    "intensity": 1.0,
+                },
+                node3_id.to_string(): {
+                    "type": "EffectNode",
+                    "name": "wwave",
+                    "input_count": 1,
+                    "intensity": 0.6,
+                    "frequency": 0.25,
+                },
+                node4_id.to_string(): {
+                    "type": "EffectNode",
+                    "name": "zoomin",
+                    "input_count": 1,
+                    "intensity": 0.3,
+                    "frequency": 1.0
+                },
+                node5_id.to_string(): {
+                    "type": "EffectNode",
+                    "name": "uvmap",
+                    "input_count": 2,
+                    "intensity": 0.2,
+                    "frequency": 0.0
+                },
+                node6_id.to_string(): {
+                    "type": "ImageNode",
+                    "name": "nyancat.gif",
+                    "intensity": 1.0,
+                },
+                output_node_id.to_string(): {
+                    "type": "ProjectionMappedOutputNode",
+                    "resolution": [1000, 1000],
+                    "screens": [
+                        {
+                            "name": "fake1",
+                            "resolution": [1920, 1080],
+                            "crop": [[0.2,0.8], [0.8,0.8], [0.8, 0.3], [0.5, 0.2], [0.2, 0.5]],
+                            "map": [1, 0.2, 0, -0.2, 1, 0, 0, 0, 1],
+                        },
+                        {
+                            "name": "fake2",
+                            "resolution": [1920, 1080],
+                            "crop": [[0.2,0.8], [0.8,0.8], [0.8, 0.3], [0.5, 0.2], [0.2, 0.5]],
+                            "map": [1, 0.2, 0, -0.2, 1, 0, 0, 0, 1],
+                        },
+                    ],
+                }
+            },
+            "time": 0.,
+            "dt": 0.03,
+        }))
+        .unwrap();
+
+        println!("Props: {}", serde_json::to_string(&props).unwrap());
+
+        // Make render targets
+        let preview_render_target_id: RenderTargetId =
+            serde_json::from_value(json!("rt_LVrjzxhXrGU7SqFo+85zkw")).unwrap();
+        let render_target_list: HashMap<RenderTargetId, RenderTarget> =
+            serde_json::from_value(json!({
+                preview_render_target_id.to_string(): {
+                    "width": 256,
+                    "height": 256,
+                    "dt": 1. / 60.
+                },
+            }))
+            .unwrap();
+
+        println!(
+            "Render target list: {}",
+            serde_json::to_string(&render_target_list).unwrap()
+        );
+
+        Self {
+            window,
+            instance,
+            adapter,
+            device,
+            queue,
+            surface,
+            config,
+            vsync_mode,
+            event_proxy,
+
+            platform,
+            egui_ctx,
+            egui_renderer,
+            screen_descriptor,
+
+            winit_output,
+            laser_output,
+
+            projector_window,
+            projector_surface,
+            projector_config,
+            projector_bind_group_layout,
+            projector_pipeline,
+            projector_node_id: output_node_id,
+
+            capture_output,
+            capture_frames,
+            capture_enabled,
+
+            mir,
+            ctx,
+            waveform_widget,
+            spectrum_widget,
+            auto_dj: None,
+            auto_dj_enabled: false,
+
+            props,
+            preview_render_target_id,
+            render_target_list,
+
+            node_add_textedit: String::new(), // TODO: factor this into its own component in ui/
+            left_panel_expanded: false,
+            node_add_wants_focus: false,
+            insertion_point: Default::default(),
+
+            waveform_texture: None,
+            spectrum_texture: None,
+            preview_textures: HashMap::new(),
+            preview_mips,
+        }
+    }
+
+    /// Passes a window event to the egui platform integration, returning true if egui
+    /// consumed it (in which case the caller shouldn't act on it further).
+    fn input(&mut self, event: &WindowEvent) -> bool {
+        self.platform.on_event(&self.egui_ctx, event).consumed
+    }
+
+    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        resize(
+            new_size,
+            &mut self.config,
+            &self.device,
+            self.surface.as_mut(),
+            Some(&mut self.screen_descriptor),
+        );
+    }
+
+    /// Drops the surface; the native window (and so the surface) doesn't survive the
+    /// app being backgrounded on Android. Everything else in `Application` keeps running.
+    fn suspend(&mut self) {
+        self.surface = None;
+    }
+
+    /// Recreates the surface after an `Event::Resumed` that followed a `suspend()`.
+    fn resume(&mut self) {
+        if self.surface.is_none() {
+            let new_surface = unsafe { self.instance.create_surface(&self.window) };
+            new_surface.configure(&self.device, &self.config);
+            self.surface = Some(new_surface);
+        }
+    }
+
+    /// Hands out a clone of the proxy a worker thread (MIDI listener, OSC server,
+    /// external beat clock) can use to post `RadianceEvent`s into the render loop.
+    fn event_proxy(&self) -> winit::event_loop::EventLoopProxy<RadianceEvent> {
+        self.event_proxy.clone()
+    }
+
+    /// Applies a new vsync preference immediately (rather than waiting for the next
+    /// resize) by re-querying supported present modes and reconfiguring the surface.
+    fn set_vsync_mode(&mut self, mode: VsyncMode) {
+        self.vsync_mode = mode;
+        if let Some(surface) = &self.surface {
+            let supported_present_modes =
+                surface.get_supported_present_modes(&self.adapter);
+            self.config.present_mode = mode.best_present_mode(&supported_present_modes);
+            surface.configure(&self.device, &self.config);
+        }
+    }
+
+    // `update()` and `render()` aren't split out yet: the per-frame work below still
+    // interleaves mutating `app.props` with the `egui_ctx.run()` UI closure, which borrows
+    // `app` a field at a time rather than as a whole. Untangling that is follow-up work;
+    // `input`/`resize`/`suspend`/`resume` are what the surface lifecycle actually needed.
+}
+
+pub async fn run() {
+    env_logger::init();
+    let event_loop = EventLoopBuilder::<RadianceEvent>::with_user_event().build();
+    let event_proxy = event_loop.create_proxy();
+    let mut app = Application::new(&event_loop, event_proxy).await;
 
     event_loop.run(move |event, event_loop, control_flow| {
-        if winit_output.on_event(&event, &event_loop, &mut ctx) {
+        if app.winit_output.on_event(&event, &event_loop, &mut app.ctx) {
             return; // Event was consumed by winit_output
         }
 
         match event {
-            Event::RedrawRequested(window_id) if window_id == window.id() => {
+            Event::Suspended => {
+                app.suspend();
+            }
+            Event::Resumed => {
+                app.resume();
+            }
+            Event::UserEvent(radiance_event) => match radiance_event {
+                RadianceEvent::BeatTick => {
+                    // Nudge a redraw right away rather than waiting for the next
+                    // MainEventsCleared, so an external clock's beat lands on this
+                    // frame instead of the one after.
+                    app.window.request_redraw();
+                }
+                RadianceEvent::MidiControlChange { cc, value } => {
+                    println!("MIDI CC {} = {}", cc, value);
+                    // TODO: route to whichever node parameter is currently mapped to
+                    // this CC once there's a parameter-mapping layer to route through.
+                }
+                RadianceEvent::OscMessage { addr, args } => {
+                    println!("OSC {} {:?}", addr, args);
+                }
+                RadianceEvent::ReloadEffect(path) => {
+                    println!("reload requested for {}", path);
+                }
+            },
+            Event::RedrawRequested(window_id) if window_id == app.window.id() => {
+                // Nothing to draw to until Resumed hands us a surface.
+                if app.surface.is_none() {
+                    return;
+                }
+
                 // Update
-                let music_info = mir.poll();
-                props.time = music_info.time;
-                props.dt = music_info.tempo * (1. / 60.);
-                props.audio = music_info.audio.clone();
-                // Merge our render list and the winit_output render list into one:
-                let render_target_list = render_target_list
+                let music_info = app.mir.poll();
+                app.props.time = music_info.time;
+                app.props.dt = music_info.tempo * (1. / 60.);
+                app.props.audio = music_info.audio.clone();
+                // Merge our render list, the winit_output render list, and the laser_output
+                // render list into one:
+                let render_target_list = app
+                    .render_target_list
                     .iter()
-                    .chain(winit_output.render_targets_iter())
+                    .chain(app.winit_output.render_targets_iter())
+                    .chain(app.laser_output.render_targets_iter())
                     .map(|(k, v)| (*k, v.clone()))
                     .collect();
-                winit_output.update(event_loop, &mut props);
-                auto_dj.as_mut().map(|a| {
-                    a.update(&mut props);
+                app.winit_output.update(event_loop, &mut app.props);
+                app.laser_output.update(&mut app.props);
+                app.auto_dj.as_mut().map(|a| {
+                    a.update(&mut app.props);
 
                     // Uncheck the checkbox if we broke the AutoDJ
                     if a.is_broken() {
-                        auto_dj_enabled = false;
+                        app.auto_dj_enabled = false;
                     }
                 });
 
-                ctx.update(&mut props, &render_target_list);
+                app.ctx.update(&mut app.props, &render_target_list);
 
                 // Paint
-                let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                    label: Some("Encoder"),
-                });
-
-                let results = ctx.paint(&mut encoder, preview_render_target_id);
+                let mut encoder = app
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                        label: Some("Encoder"),
+                    });
 
-                let preview_images: HashMap<NodeId, egui::TextureId> = props
-                    .graph
-                    .nodes
-                    .iter()
-                    .map(|&node_id| {
-                        let tex_id = egui_renderer.register_native_texture(
-                            &device,
-                            &results.get(&node_id).unwrap().view,
-                            wgpu::FilterMode::Linear,
-                        );
-                        (node_id, tex_id)
-                    })
-                    .collect();
+                let results = app.ctx.paint(&mut encoder, app.preview_render_target_id);
 
                 // Update & paint widgets
 
@@ -375,38 +758,81 @@ pub async fn run() {
                     }
                 }
 
+                // Update each live node's preview texture in place (reusing its `TextureId`)
+                // instead of freeing and re-registering every preview every frame, then drop
+                // the registry entries for any node that's gone since last frame. Each preview
+                // is routed through `preview_mips` first, so the tile's thumbnail (much smaller
+                // than the render target it's sampled from) downsamples from a real mip chain
+                // instead of aliasing.
+                let preview_size = {
+                    let render_target = app.render_target_list.get(&app.preview_render_target_id).unwrap();
+                    (render_target.width, render_target.height)
+                };
+                for &node_id in app.props.graph.nodes.iter() {
+                    let source_texture = &results.get(&node_id).unwrap().texture;
+                    let native_texture = app.preview_mips.refresh(node_id, source_texture, preview_size);
+                    let mut texture_id = app.preview_textures.remove(&node_id);
+                    update_or_register_native_texture(
+                        &mut app.egui_renderer,
+                        &app.device,
+                        native_texture,
+                        &mut texture_id,
+                    );
+                    app.preview_textures.insert(node_id, texture_id.unwrap());
+                }
+                let current_nodes: std::collections::HashSet<NodeId> =
+                    app.props.graph.nodes.iter().copied().collect();
+                app.preview_mips.retain(|node_id| current_nodes.contains(node_id));
+                let removed_previews: Vec<NodeId> = app
+                    .preview_textures
+                    .keys()
+                    .copied()
+                    .filter(|node_id| !current_nodes.contains(node_id))
+                    .collect();
+                for node_id in removed_previews {
+                    if let Some(texture_id) = app.preview_textures.remove(&node_id) {
+                        app.egui_renderer.free_texture(&texture_id);
+                    }
+                }
+                let preview_images = app.preview_textures.clone();
+
                 let waveform_size = egui::vec2(330., 65.);
-                let waveform_native_texture = waveform_widget.paint(
+                let waveform_native_texture = app.waveform_widget.paint(
                     waveform_size,
                     &music_info.audio,
                     music_info.uncompensated_time,
                 );
 
                 update_or_register_native_texture(
-                    &mut egui_renderer,
-                    &device,
+                    &mut app.egui_renderer,
+                    &app.device,
                     &waveform_native_texture.view,
-                    &mut waveform_texture,
+                    &mut app.waveform_texture,
                 );
 
                 let spectrum_size = egui::vec2(330., 65.);
                 let spectrum_native_texture =
-                    spectrum_widget.paint(spectrum_size, &music_info.spectrum);
+                    app.spectrum_widget.paint(spectrum_size, &music_info.spectrum);
 
                 update_or_register_native_texture(
-                    &mut egui_renderer,
-                    &device,
+                    &mut app.egui_renderer,
+                    &app.device,
                     &spectrum_native_texture.view,
-                    &mut spectrum_texture,
+                    &mut app.spectrum_texture,
                 );
 
                 // EGUI update
-                let raw_input = platform.take_egui_input(&window);
+                let raw_input = app.platform.take_egui_input(&app.window);
+                let egui_ctx = app.egui_ctx.clone();
+                let waveform_texture = app.waveform_texture;
+                let spectrum_texture = app.spectrum_texture;
+                let node_states = app.ctx.node_states();
+                let mut vsync_mode = app.vsync_mode;
                 let full_output = egui_ctx.run(raw_input, |egui_ctx| {
                     let left_panel_response = egui::SidePanel::left("left").show_animated(
                         egui_ctx,
-                        left_panel_expanded,
-                        |ui| ui.text_edit_singleline(&mut node_add_textedit),
+                        app.left_panel_expanded,
+                        |ui| ui.text_edit_singleline(&mut app.node_add_textedit),
                     );
 
                     let full_rect = egui_ctx.available_rect();
@@ -419,21 +845,33 @@ pub async fn run() {
                             ui.horizontal(|ui| {
                                 ui.image(waveform_texture.unwrap(), waveform_size);
                                 ui.image(spectrum_texture.unwrap(), spectrum_size);
-                                ui.checkbox(&mut auto_dj_enabled, "Auto DJ");
+                                ui.checkbox(&mut app.auto_dj_enabled, "Auto DJ");
+                                ui.checkbox(&mut app.capture_enabled, "Record");
+                                egui::ComboBox::from_label("Vsync")
+                                    .selected_text(format!("{:?}", vsync_mode))
+                                    .show_ui(ui, |ui| {
+                                        ui.selectable_value(&mut vsync_mode, VsyncMode::On, "On");
+                                        ui.selectable_value(
+                                            &mut vsync_mode,
+                                            VsyncMode::LowLatency,
+                                            "Low latency",
+                                        );
+                                        ui.selectable_value(&mut vsync_mode, VsyncMode::Off, "Off");
+                                    });
                             });
 
-                            let mosaic_response = ui.add(mosaic(
+                            let (mosaic_response, _mosaic_events) = mosaic_ui(
+                                ui,
                                 "mosaic",
-                                &mut props,
-                                ctx.node_states(),
+                                &mut app.props,
+                                node_states,
                                 &preview_images,
-                                &mut insertion_point,
-                                modal_id,
-                            ));
+                                &mut app.insertion_point,
+                            );
 
-                            if !left_panel_expanded && ui.input().key_pressed(egui::Key::A) {
-                                left_panel_expanded = true;
-                                node_add_wants_focus = true;
+                            if !app.left_panel_expanded && ui.input().key_pressed(egui::Key::A) {
+                                app.left_panel_expanded = true;
+                                app.node_add_wants_focus = true;
                             }
 
                             if let Some(egui::InnerResponse {
@@ -443,13 +881,13 @@ pub async fn run() {
                             {
                                 // TODO all this side-panel handling is wonky. It is done, in part, to avoid mutating the props before it's drawn.
                                 // This needs to be factored out into a real "library" component.
-                                if node_add_wants_focus {
+                                if app.node_add_wants_focus {
                                     node_add_response.request_focus();
-                                    node_add_wants_focus = false;
+                                    app.node_add_wants_focus = false;
                                 }
                                 if node_add_response.lost_focus() {
                                     if egui_ctx.input().key_pressed(egui::Key::Enter) {
-                                        let node_add_textedit_str = node_add_textedit.as_str();
+                                        let node_add_textedit_str = app.node_add_textedit.as_str();
                                         if node_add_textedit_str.starts_with("http:")
                                             || node_add_textedit_str.starts_with("https:")
                                             || node_add_textedit_str.ends_with(".mp4")
@@ -459,13 +897,13 @@ pub async fn run() {
                                         {
                                             let new_node_id = NodeId::gen();
                                             let new_node_props = NodeProps::MovieNode(MovieNodeProps {
-                                                name: node_add_textedit.clone(),
+                                                name: app.node_add_textedit.clone(),
                                                 ..Default::default()
                                             });
-                                            props.node_props.insert(new_node_id, new_node_props);
-                                            props.graph.insert_node(new_node_id, &insertion_point);
+                                            app.props.node_props.insert(new_node_id, new_node_props);
+                                            app.props.graph.insert_node(new_node_id, &app.insertion_point);
                                         } else {
-                                            match node_add_textedit.as_str() {
+                                            match app.node_add_textedit.as_str() {
                                                 "ScreenOutput" => {
                                                     let new_node_id = NodeId::gen();
                                                     let new_node_props = NodeProps::ScreenOutputNode(
@@ -473,12 +911,12 @@ pub async fn run() {
                                                             ..Default::default()
                                                         },
                                                     );
-                                                    props
+                                                    app.props
                                                         .node_props
                                                         .insert(new_node_id, new_node_props);
-                                                    props
+                                                    app.props
                                                         .graph
-                                                        .insert_node(new_node_id, &insertion_point);
+                                                        .insert_node(new_node_id, &app.insertion_point);
                                                 }
                                                 "ProjectionMappedOutput" => {
                                                     let new_node_id = NodeId::gen();
@@ -487,34 +925,34 @@ pub async fn run() {
                                                             ..Default::default()
                                                         },
                                                     );
-                                                    props
+                                                    app.props
                                                         .node_props
                                                         .insert(new_node_id, new_node_props);
-                                                    props
+                                                    app.props
                                                         .graph
-                                                        .insert_node(new_node_id, &insertion_point);
+                                                        .insert_node(new_node_id, &app.insertion_point);
                                                 }
                                                 _ => {
                                                     let new_node_id = NodeId::gen();
                                                     let new_node_props =
                                                         NodeProps::EffectNode(EffectNodeProps {
-                                                            name: node_add_textedit.clone(),
+                                                            name: app.node_add_textedit.clone(),
                                                             ..Default::default()
                                                         });
-                                                    props
+                                                    app.props
                                                         .node_props
                                                         .insert(new_node_id, new_node_props);
-                                                    props
+                                                    app.props
                                                         .graph
-                                                        .insert_node(new_node_id, &insertion_point);
+                                                        .insert_node(new_node_id, &app.insertion_point);
                                                     // TODO: select and focus the new node
                                                     // (consider making selection & focus part of the explicit state of mosaic, not memory)
                                                 }
                                             }
                                         }
                                     }
-                                    node_add_textedit.clear();
-                                    left_panel_expanded = false;
+                                    app.node_add_textedit.clear();
+                                    app.left_panel_expanded = false;
                                     mosaic_response.request_focus();
                                 }
                             }
@@ -524,8 +962,8 @@ pub async fn run() {
                             ui.allocate_ui_at_rect(full_rect, |ui| {
                                 ui.add(modal(
                                     modal_id,
-                                    &mut props,
-                                    ctx.node_states(),
+                                    &mut app.props,
+                                    app.ctx.node_states(),
                                     &preview_images,
                                 ));
                             });
@@ -534,20 +972,26 @@ pub async fn run() {
                 });
 
                 // Construct or destroy the AutoDJ
-                match (auto_dj_enabled, &mut auto_dj) {
+                match (app.auto_dj_enabled, &mut app.auto_dj) {
                     (false, Some(_)) => {
-                        auto_dj = None;
+                        app.auto_dj = None;
                     }
                     (true, None) => {
-                        auto_dj = Some(AutoDJ::new());
+                        app.auto_dj = Some(AutoDJ::new());
                     }
                     _ => {}
                 }
 
-                platform.handle_platform_output(&window, &egui_ctx, full_output.platform_output);
-                let clipped_primitives = egui_ctx.tessellate(full_output.shapes); // create triangles to paint
+                if vsync_mode != app.vsync_mode {
+                    app.set_vsync_mode(vsync_mode);
+                }
+
+                app.platform
+                    .handle_platform_output(&app.window, &app.egui_ctx, full_output.platform_output);
+                let clipped_primitives = app.egui_ctx.tessellate(full_output.shapes); // create triangles to paint
 
                 // EGUI paint
+                let surface = app.surface.as_ref().unwrap();
                 let output = surface.get_current_texture().unwrap();
                 let view = output
                     .texture
@@ -556,14 +1000,15 @@ pub async fn run() {
                 // Upload all resources for the GPU.
                 let tdelta: egui::TexturesDelta = full_output.textures_delta;
                 for (texture_id, image_delta) in tdelta.set.iter() {
-                    egui_renderer.update_texture(&device, &queue, *texture_id, image_delta);
+                    app.egui_renderer
+                        .update_texture(&app.device, &app.queue, *texture_id, image_delta);
                 }
-                egui_renderer.update_buffers(
-                    &device,
-                    &queue,
+                app.egui_renderer.update_buffers(
+                    &app.device,
+                    &app.queue,
                     &mut encoder,
                     &clipped_primitives,
-                    &screen_descriptor,
+                    &app.screen_descriptor,
                 );
 
                 // Record UI render pass.
@@ -587,68 +1032,146 @@ pub async fn run() {
                             depth_stencil_attachment: None,
                         });
 
-                    egui_renderer.render(
+                    app.egui_renderer.render(
                         &mut egui_render_pass,
                         &clipped_primitives,
-                        &screen_descriptor,
+                        &app.screen_descriptor,
+                    );
+                }
+
+                // Draw the same composited output node to the dedicated projector window,
+                // clean of the node editor UI, in the same command buffer as the main window.
+                let mut projector_present: Option<wgpu::SurfaceTexture> = None;
+                if let Some(result) = results.get(&app.projector_node_id) {
+                    if let Ok(projector_output) = app.projector_surface.get_current_texture() {
+                        let projector_view = projector_output
+                            .texture
+                            .create_view(&wgpu::TextureViewDescriptor::default());
+                        let projector_bind_group =
+                            app.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                                layout: &app.projector_bind_group_layout,
+                                entries: &[
+                                    wgpu::BindGroupEntry {
+                                        binding: 0,
+                                        resource: wgpu::BindingResource::TextureView(&result.view),
+                                    },
+                                    wgpu::BindGroupEntry {
+                                        binding: 1,
+                                        resource: wgpu::BindingResource::Sampler(&result.sampler),
+                                    },
+                                ],
+                                label: Some("projector output bind group"),
+                            });
+
+                        {
+                            let mut projector_render_pass =
+                                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                                    label: Some("Projector output render pass"),
+                                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                                        view: &projector_view,
+                                        resolve_target: None,
+                                        ops: wgpu::Operations {
+                                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                                            store: true,
+                                        },
+                                    })],
+                                    depth_stencil_attachment: None,
+                                });
+                            projector_render_pass.set_pipeline(&app.projector_pipeline);
+                            projector_render_pass.set_bind_group(0, &projector_bind_group, &[]);
+                            projector_render_pass.draw(0..4, 0..1);
+                        }
+
+                        projector_present = Some(projector_output);
+                    }
+                }
+
+                // Before presenting: copy this frame out for recording/streaming, independent
+                // of whatever the window ends up showing.
+                if app.capture_enabled {
+                    app.capture_output.capture(
+                        &mut encoder,
+                        &output.texture,
+                        app.config.width,
+                        app.config.height,
                     );
                 }
 
                 // Submit the commands.
-                queue.submit(iter::once(encoder.finish()));
+                app.queue.submit(iter::once(encoder.finish()));
+
+                if app.capture_enabled {
+                    app.capture_output.finish_frame();
+                    // TODO: route these to a video encoder or a projection output once one
+                    // exists; for now just keep the channel from backing up.
+                    for CaptureFrame { width, height, rgba } in app.capture_frames.try_iter() {
+                        println!("captured frame {}x{} ({} bytes)", width, height, rgba.len());
+                    }
+                }
 
                 // Draw
                 output.present();
-
-                // Clear out all native textures for the next frame
-                for texture_id in preview_images.values() {
-                    egui_renderer.free_texture(texture_id);
+                if let Some(projector_output) = projector_present {
+                    projector_output.present();
                 }
 
-                // Clear out egui textures for the next frame
+                // Preview textures are freed only when their node disappears (see the
+                // registry update above), not every frame.
+
+                // Clear out egui textures egui itself says it's done with for the next frame
                 for texture_id in tdelta.free.iter() {
-                    egui_renderer.free_texture(texture_id);
+                    app.egui_renderer.free_texture(texture_id);
                 }
             }
             Event::MainEventsCleared => {
                 // RedrawRequested will only trigger once, unless we manually
                 // request it.
-                window.request_redraw();
+                app.window.request_redraw();
             }
             Event::WindowEvent {
                 ref event,
                 window_id,
-            } if window_id == window.id() => {
-                if true {
-                    // XXX
-                    // Pass the winit events to the EGUI platform integration.
-                    if platform.on_event(&egui_ctx, event).consumed {
-                        return; // EGUI wants exclusive use of this event
+            } if window_id == app.window.id() => {
+                if app.input(event) {
+                    return; // EGUI wants exclusive use of this event
+                }
+                match event {
+                    WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                    WindowEvent::Resized(physical_size) => {
+                        app.resize(*physical_size);
                     }
-                    match event {
-                        WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-                        WindowEvent::Resized(physical_size) => {
-                            let size = *physical_size;
-                            resize(
-                                size,
-                                &mut config,
-                                &device,
-                                &mut surface,
-                                Some(&mut screen_descriptor),
-                            );
+                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                        // new_inner_size is &&mut so we have to dereference it twice
+                        app.resize(**new_inner_size);
+                    }
+                    _ => {}
+                }
+            }
+            Event::WindowEvent {
+                ref event,
+                window_id,
+            } if window_id == app.projector_window.id() => {
+                match event {
+                    // Deliberately ignore CloseRequested here: closing the projector window
+                    // shouldn't end the live set, just stop showing it anywhere.
+                    WindowEvent::Resized(physical_size) => {
+                        if physical_size.width > 0 && physical_size.height > 0 {
+                            app.projector_config.width = physical_size.width;
+                            app.projector_config.height = physical_size.height;
+                            app.projector_surface
+                                .configure(&app.device, &app.projector_config);
                         }
-                        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                            let size = **new_inner_size;
-                            resize(
-                                size,
-                                &mut config,
-                                &device,
-                                &mut surface,
-                                Some(&mut screen_descriptor),
-                            );
+                    }
+                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                        let size = **new_inner_size;
+                        if size.width > 0 && size.height > 0 {
+                            app.projector_config.width = size.width;
+                            app.projector_config.height = size.height;
+                            app.projector_surface
+                                .configure(&app.device, &app.projector_config);
                         }
-                        _ => {}
                     }
+                    _ => {}
                 }
             }
             _ => {}
@@ -656,6 +1179,17 @@ pub async fn run() {
     });
 }
 
+#[cfg(not(target_os = "android"))]
 pub fn main() {
     pollster::block_on(run());
 }
+
+// Android doesn't call `main`; `android_main` is the entry point the APK's native
+// activity looks for. `run()`'s `EventLoopBuilder::build()` picks up the app handle ndk-glue
+// stashes away for it, so there's nothing else to thread through here.
+#[cfg(target_os = "android")]
+#[no_mangle]
+fn android_main() {
+    android_logger::init_once(android_logger::Config::default().with_min_level(log::Level::Info));
+    pollster::block_on(run());
+}
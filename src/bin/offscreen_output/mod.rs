@@ -0,0 +1,218 @@
+/// Headless counterpart to `winit_output`: services `OffscreenOutputNode`s that have no
+/// window or surface at all. Each `step()` paints every known node's render target and
+/// streams the result to disk as a numbered PNG, the same copy_texture_to_buffer + map_async
+/// + device.poll() round-trip `winit_output::WinitOutput::capture` uses, just driven by a
+/// caller-advanced `time` instead of a `RedrawRequested` event. This lets a CLI tool
+/// batch-render a graph deterministically at a fixed `dt` without ever opening an event loop.
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub enum OffscreenError {
+    /// The readback buffer's `map_async` resolved to an error.
+    MapFailed,
+    /// Writing the PNG to `output_dir` failed.
+    Encode(String),
+}
+
+impl std::fmt::Display for OffscreenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            OffscreenError::MapFailed => write!(f, "readback buffer map_async failed"),
+            OffscreenError::Encode(details) => write!(f, "PNG encode failed: {}", details),
+        }
+    }
+}
+
+impl std::error::Error for OffscreenError {}
+
+pub type OffscreenResult<T> = std::result::Result<T, OffscreenError>;
+
+#[derive(Debug)]
+struct OffscreenOutput {
+    // Cached props
+    output_dir: PathBuf,
+
+    // Resources
+    render_target_id: radiance::RenderTargetId,
+    render_target: radiance::RenderTarget,
+
+    // Internal
+    frame_index: u64,
+    initial_update: bool,
+}
+
+pub struct OffscreenOutputManager {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    offscreen_outputs: HashMap<radiance::NodeId, OffscreenOutput>,
+}
+
+impl OffscreenOutputManager {
+    pub fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>) -> Self {
+        OffscreenOutputManager {
+            device,
+            queue,
+            offscreen_outputs: HashMap::new(),
+        }
+    }
+
+    pub fn render_targets_iter(&self) -> impl Iterator<Item = (&radiance::RenderTargetId, &radiance::RenderTarget)> {
+        self.offscreen_outputs
+            .values()
+            .map(|offscreen_output| (&offscreen_output.render_target_id, &offscreen_output.render_target))
+    }
+
+    /// Creates/prunes `OffscreenOutput`s to match `radiance::NodeProps::OffscreenOutputNode`s
+    /// in `props`, and refreshes each one's cached output directory, exactly as
+    /// `WinitOutput::update` does for screens.
+    pub fn update(&mut self, props: &mut radiance::Props) {
+        for offscreen_output in self.offscreen_outputs.values_mut() {
+            offscreen_output.initial_update = true;
+        }
+
+        self.offscreen_outputs.retain(|id, _| {
+            props
+                .node_props
+                .get(id)
+                .map(|node_props| matches!(node_props, radiance::NodeProps::OffscreenOutputNode(_)))
+                .unwrap_or(false)
+        });
+
+        for (node_id, node_props) in props.node_props.iter() {
+            if let radiance::NodeProps::OffscreenOutputNode(_) = node_props {
+                if !self.offscreen_outputs.contains_key(node_id) {
+                    self.offscreen_outputs.insert(*node_id, Self::new_offscreen_output());
+                }
+            }
+        }
+
+        for (node_id, offscreen_output) in self.offscreen_outputs.iter_mut() {
+            let offscreen_output_props: &mut radiance::OffscreenOutputNodeProps =
+                props.node_props.get_mut(node_id).unwrap().try_into().unwrap();
+            offscreen_output.output_dir = PathBuf::from(&offscreen_output_props.output_dir);
+        }
+    }
+
+    fn new_offscreen_output() -> OffscreenOutput {
+        let render_target_id = radiance::RenderTargetId::gen();
+        let render_target: radiance::RenderTarget = serde_json::from_value(serde_json::json!({
+            "width": 1920,
+            "height": 1080,
+            "dt": 1. / 60.
+        }))
+        .unwrap();
+
+        OffscreenOutput {
+            output_dir: PathBuf::new(),
+            render_target_id,
+            render_target,
+            frame_index: 0,
+            initial_update: false,
+        }
+    }
+
+    /// Advances `props.time` to `time`, paints every known offscreen node's render target, and
+    /// writes each as the next numbered PNG in its `output_dir`. Call once per frame of a
+    /// fixed-`dt` batch render; painting is gated on `initial_update`, same as `WinitOutput`,
+    /// so a freshly-created node's first frame isn't attempted before the context knows its
+    /// render target.
+    pub fn step(&mut self, props: &mut radiance::Props, ctx: &mut radiance::Context, time: f64) -> OffscreenResult<()> {
+        props.time = time;
+
+        for (node_id, offscreen_output) in self.offscreen_outputs.iter_mut() {
+            if !offscreen_output.initial_update {
+                continue;
+            }
+
+            let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Offscreen Output Encoder"),
+            });
+
+            let results = ctx.paint(&mut encoder, offscreen_output.render_target_id);
+            let Some(result) = results.get(node_id) else {
+                continue;
+            };
+
+            let width = offscreen_output.render_target.width;
+            let height = offscreen_output.render_target.height;
+
+            // `copy_texture_to_buffer` requires each row be a multiple of
+            // `COPY_BYTES_PER_ROW_ALIGNMENT` (256 bytes); pad rather than assume `width * 4`
+            // is already aligned.
+            let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+            let unpadded_bytes_per_row = width * 4;
+            let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+
+            let readback_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Offscreen readback buffer"),
+                size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+
+            encoder.copy_texture_to_buffer(
+                wgpu::ImageCopyTexture {
+                    texture: &result.texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::ImageCopyBuffer {
+                    buffer: &readback_buffer,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: Some(padded_bytes_per_row),
+                        rows_per_image: None,
+                    },
+                },
+                wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+            );
+
+            self.queue.submit(std::iter::once(encoder.finish()));
+
+            let slice = readback_buffer.slice(..);
+            let (map_tx, map_rx) = std::sync::mpsc::channel();
+            slice.map_async(wgpu::MapMode::Read, move |result| {
+                let _ = map_tx.send(result);
+            });
+            self.device.poll(wgpu::Maintain::Wait);
+
+            let Ok(Ok(())) = map_rx.recv() else {
+                return Err(OffscreenError::MapFailed);
+            };
+
+            let padded = slice.get_mapped_range();
+            let unpadded_bytes_per_row = unpadded_bytes_per_row as usize;
+            let mut rgba = Vec::with_capacity(unpadded_bytes_per_row * height as usize);
+            for row in padded.chunks(padded_bytes_per_row as usize) {
+                rgba.extend_from_slice(&row[..unpadded_bytes_per_row]);
+            }
+            drop(padded);
+            readback_buffer.unmap();
+
+            std::fs::create_dir_all(&offscreen_output.output_dir)
+                .map_err(|e| OffscreenError::Encode(e.to_string()))?;
+            let frame_path = offscreen_output
+                .output_dir
+                .join(format!("frame_{:06}.png", offscreen_output.frame_index));
+            image::save_buffer(&frame_path, &rgba, width, height, image::ColorType::Rgba8)
+                .map_err(|e| OffscreenError::Encode(e.to_string()))?;
+            offscreen_output.frame_index += 1;
+        }
+
+        Ok(())
+    }
+
+    /// No-op for the PNG-sequence path: each frame is written to disk as `step()` goes.
+    /// Kept as a symmetrical finalize point for a future muxer-backed output, which would
+    /// need to flush/close its encoder here.
+    pub fn flush(&mut self) -> OffscreenResult<()> {
+        Ok(())
+    }
+}
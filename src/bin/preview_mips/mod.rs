@@ -0,0 +1,217 @@
+// Each node tile in the mosaic UI shows a shrunk-down live preview of that node's rendered
+// output, sampled straight from the render target `radiance::Context::paint` hands back -
+// which only ever has one mip level, so a tile scaled well below its render target's
+// resolution (the common case) reads aliased rather than smoothly downsampled, no matter what
+// `FilterMode` `update_or_register_native_texture` asks egui to sample it with. This keeps, per
+// node, a second copy of its output with a full mip chain, refreshed each frame: a
+// texture-to-texture copy into the base level, then a GPU blit pass per mip level (sampling the
+// level below through a linear filter).
+
+use radiance::texture_builder::mip_level_count;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+struct Preview {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    size: (u32, u32),
+}
+
+/// Maintains a mipmapped copy of each node's live preview render target, keyed by node id, so
+/// `EffectNodeTile`'s thumbnail downsamples cleanly instead of aliasing.
+pub struct PreviewMipGenerator {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+    previews: HashMap<radiance::NodeId, Preview>,
+}
+
+impl PreviewMipGenerator {
+    pub fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>) -> Self {
+        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Preview mip blit shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("blit.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("preview mip blit bind group layout"),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Preview Mip Blit Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Preview Mip Blit Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        PreviewMipGenerator {
+            device,
+            queue,
+            bind_group_layout,
+            pipeline,
+            sampler,
+            previews: HashMap::new(),
+        }
+    }
+
+    /// Refreshes `node_id`'s mipmapped preview copy from `source` (its live render target
+    /// texture, `size` pixels), (re)allocating the copy first if it's missing or `size` has
+    /// changed, then returns a view of the whole mip chain to register with egui.
+    pub fn refresh(&mut self, node_id: radiance::NodeId, source: &wgpu::Texture, size: (u32, u32)) -> &wgpu::TextureView {
+        let needs_realloc = self.previews.get(&node_id).map_or(true, |preview| preview.size != size);
+        if needs_realloc {
+            let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("preview mip texture"),
+                size: wgpu::Extent3d {
+                    width: size.0,
+                    height: size.1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: mip_level_count(size),
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8UnormSrgb,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            self.previews.insert(node_id, Preview { texture, view, size });
+        }
+
+        let preview = self.previews.get(&node_id).unwrap();
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("preview mip generation encoder"),
+        });
+
+        encoder.copy_texture_to_texture(
+            wgpu::ImageCopyTexture {
+                texture: source,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyTexture {
+                texture: &preview.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::Extent3d {
+                width: size.0,
+                height: size.1,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        for level in 1..mip_level_count(size) {
+            let src_view = preview.texture.create_view(&wgpu::TextureViewDescriptor {
+                base_mip_level: level - 1,
+                mip_level_count: Some(1),
+                ..Default::default()
+            });
+            let dst_view = preview.texture.create_view(&wgpu::TextureViewDescriptor {
+                base_mip_level: level,
+                mip_level_count: Some(1),
+                ..Default::default()
+            });
+
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&src_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+                label: Some("preview mip blit bind group"),
+            });
+
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("preview mip blit pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &dst_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..4, 0..1);
+        }
+
+        self.queue.submit(Some(encoder.finish()));
+
+        &self.previews.get(&node_id).unwrap().view
+    }
+
+    /// Drops preview copies for nodes no longer present, mirroring how `Application` prunes
+    /// `preview_textures` of removed nodes each frame.
+    pub fn retain(&mut self, mut keep: impl FnMut(&radiance::NodeId) -> bool) {
+        self.previews.retain(|node_id, _| keep(node_id));
+    }
+}
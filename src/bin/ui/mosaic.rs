@@ -1,5 +1,5 @@
 use radiance::{Props, Graph, NodeId, NodeProps, CommonNodeProps, NodeState, InsertionPoint};
-use egui::{pos2, vec2, Rect, Ui, Widget, Response, InnerResponse, Vec2, Sense, Pos2, TextureId, Modifiers, IdMap, InputState};
+use egui::{pos2, vec2, Color32, CursorIcon, LayerId, Order, Rect, Shape, Stroke, Ui, Response, InnerResponse, Vec2, Sense, Pos2, TextureId, Modifiers, IdMap, InputState};
 use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::hash::Hash;
@@ -10,6 +10,29 @@ const MARGIN: f32 = 20.;
 const MOSAIC_ANIMATION_DURATION: f32 = 0.5;
 const INTENSITY_SCROLL_RATE: f32 = 0.001;
 
+/// How long a cancelled drag takes to ease back to its origin (Escape, or an errant background
+/// click mid-drag). Deliberately shorter than `MOSAIC_ANIMATION_DURATION`, since this is
+/// correcting a mistake rather than settling into a new layout.
+const DRAG_CANCEL_DURATION: f32 = 0.25;
+
+/// Minimum accumulated pointer displacement (points) a tile press must cover before it's
+/// promoted from a pending click to a drag.
+const DRAG_START_THRESHOLD: f32 = 6.;
+
+/// Minimum pointer displacement (mosaic-local points) a press-and-drag on empty background must
+/// cover before it turns into a rubber-band selection rather than resolving as a plain click.
+const BAND_SELECT_THRESHOLD: f32 = 4.;
+
+const BAND_SELECT_FILL: Color32 = Color32::from_rgba_premultiplied(80, 130, 220, 60);
+const BAND_SELECT_STROKE: Color32 = Color32::from_rgb(80, 130, 220);
+
+/// Backdrop painted behind each dragged tile's ghost, so the translucent preview image reads
+/// against whatever happens to be under the cursor instead of just floating in empty space.
+const DRAG_GHOST_BACKDROP: Color32 = Color32::from_rgba_premultiplied(20, 20, 20, 60);
+/// Tint applied to a tile's preview image when painting its drag ghost; the alpha is what makes
+/// the ghost read as "in flight" rather than a fully-opaque duplicate tile.
+const DRAG_GHOST_TINT: Color32 = Color32::from_rgba_premultiplied(255, 255, 255, 160);
+
 /// A struct to hold info about a single tile that has been laid out.
 #[derive(Clone, Debug)]
 struct TileInMosaic {
@@ -432,12 +455,93 @@ struct LayoutCache {
     tiles: Vec<TileInMosaic>,
 }
 
+/// Every `NodeId` reachable from `start` by following the graph's edges in either direction
+/// (treating them as undirected), including `start` itself. Used to find the rest of the tiles
+/// that should be dragged along with a grabbed tile: its whole connected subgraph.
+fn connected_component(start: NodeId, props: &Props) -> HashSet<NodeId> {
+    let (_, input_mapping) = props.graph.mapping();
+
+    let mut adjacency: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+    for (&node, inputs) in input_mapping.iter() {
+        for &input_node in inputs.iter().flatten() {
+            adjacency.entry(node).or_default().push(input_node);
+            adjacency.entry(input_node).or_default().push(node);
+        }
+    }
+
+    let mut visited = HashSet::new();
+    let mut stack = vec![start];
+    visited.insert(start);
+    while let Some(node) = stack.pop() {
+        for &neighbor in adjacency.get(&node).into_iter().flatten() {
+            if visited.insert(neighbor) {
+                stack.push(neighbor);
+            }
+        }
+    }
+    visited
+}
+
 /// State associated with dragged tiles
 #[derive(Debug)]
 struct DragMemory {
     pub target: TileId,
     pub contingent: HashSet<TileId>,
+    /// Total pointer displacement since the drag started; shared by every tile in `contingent`
+    /// so the whole group moves together.
     pub offset: Vec2,
+    /// Set once the drag is being cancelled rather than dropped: the offset every contingent
+    /// tile had when cancellation began (its "pre-drag" value to ease back to zero from), and
+    /// the time it began, in `ui.input().time` units.
+    pub cancelling: Option<(Vec2, f64)>,
+}
+
+/// Starts easing `drag`'s tiles back to their origin, unless it's already cancelling. Returns
+/// whether cancellation actually began just now (as opposed to already being in progress), so
+/// callers know whether to emit a `MosaicEvent::Cancel`.
+fn begin_drag_cancel(drag: &mut DragMemory, time: f64) -> bool {
+    if drag.cancelling.is_none() {
+        drag.cancelling = Some((drag.offset, time));
+        true
+    } else {
+        false
+    }
+}
+
+/// A mosaic interaction, in the exact order it happened this frame.
+///
+/// Selection and drag state both live in `MosaicMemory`, but callers that want to react to
+/// drops (e.g. graph reconnection at the focused tile's `InsertionPoint`), group undo/redo per
+/// drag, or sync selection elsewhere, need to see what *happened*, not just peek at the end
+/// state. Every transition `mosaic_ui` makes is routed through this list instead of being
+/// reconstructed from raw `Response`s, so it's the single source of truth for "what happened".
+///
+/// Emission order within a frame is guaranteed: a press is always `Pressed` before anything
+/// else happens to it; `Drag` only follows a `DragStart`; a `Drop` is always emitted before the
+/// `DragEnd` that follows it, so reconnection logic can act on the drop before cleanup runs; and
+/// `Clicked` is only emitted when no drag occurred (a drag's initial click-to-select is folded
+/// into `DragStart`, not reported separately).
+#[derive(Clone, Debug, PartialEq)]
+pub enum MosaicEvent {
+    /// A tile was pressed (button down), before it's known whether this becomes a click or a drag.
+    Pressed(TileId),
+    /// A tile was pressed and released without crossing the drag threshold.
+    Clicked(TileId),
+    /// A press crossed the drag threshold and became a drag of `contingent` (the grabbed tile's
+    /// whole connected subgraph, or just itself if Alt was held).
+    DragStart { target: TileId, contingent: HashSet<TileId> },
+    /// The drag's tiles moved by `delta` (mosaic-local points) this frame.
+    Drag(Vec2),
+    /// The drag was released (not cancelled) and committed; the tiles settle back into their
+    /// laid-out positions without the cancel snap-back.
+    Drop,
+    /// The drag (or its cancellation) is finished; `MosaicMemory::drag` has been cleared.
+    DragEnd,
+    /// An in-progress drag started easing back to its origin (Escape, or an errant background
+    /// click mid-drag). A `DragEnd` follows once the snap-back animation completes.
+    Cancel,
+    /// The set of selected nodes changed this frame.
+    SelectionChanged(HashSet<NodeId>),
 }
 
 /// State associated with the mosaic UI, to be preserved between frames,
@@ -450,6 +554,14 @@ struct MosaicMemory {
     pub focused: Option<TileId>,
     pub drag: Option<DragMemory>,
 
+    /// Start point (mosaic-local coordinates) of an in-progress rubber-band selection, set when
+    /// the background is pressed and cleared once the press is released.
+    pub band_start: Option<Pos2>,
+
+    /// The tile currently being pressed, and its accumulated pointer displacement since that
+    /// press began, while it hasn't yet crossed `DRAG_START_THRESHOLD` to become a real drag.
+    pub press: Option<(TileId, Vec2)>,
+
     layout_cache: Option<LayoutCache>,
 }
 
@@ -460,7 +572,7 @@ pub fn mosaic_ui<IdSource>(
     node_states: &HashMap<NodeId, NodeState>,
     preview_images: &HashMap<NodeId, TextureId>,
     insertion_point: &mut InsertionPoint,
-) -> Response
+) -> (Response, Vec<MosaicEvent>)
     where IdSource: Hash + std::fmt::Debug,
 {
     // Generate an UI ID for the mosiac
@@ -472,6 +584,12 @@ pub fn mosaic_ui<IdSource>(
 
     let mut mosaic_memory = mosaic_memory.lock().unwrap();
 
+    // Every state transition below is also appended to `events`, in the order it happens, so
+    // that it's returned as the single source of truth alongside `mosaic_response` instead of
+    // callers re-deriving it from raw `Response`s.
+    let mut events: Vec<MosaicEvent> = Vec::new();
+    let selected_before = mosaic_memory.selected.clone();
+
     props.fix();
 
     // Lay out the mosaic
@@ -510,6 +628,10 @@ pub fn mosaic_ui<IdSource>(
         };
         if abort_drag {
             mosaic_memory.drag = None;
+            // The drag's target tile vanished out from under it rather than being released or
+            // cancelled normally; still terminate it through the event stream so consumers
+            // tracking drag state (e.g. undo/redo grouping) don't see an unterminated DragStart.
+            events.push(MosaicEvent::DragEnd);
         }
     }
 
@@ -518,9 +640,37 @@ pub fn mosaic_ui<IdSource>(
     let layout_size = *layout_size;
     let tiles = tiles.to_vec();
 
-    let (mosaic_rect, mosaic_response) = ui.allocate_exact_size(layout_size, Sense::click());
+    let (mosaic_rect, mosaic_response) = ui.allocate_exact_size(layout_size, Sense::click_and_drag());
+
+    // Rubber-band (marquee) selection: a press-and-drag that starts on empty background (tiles
+    // have their own Sense, so a press on a tile never reaches `mosaic_response`) begins a band
+    // whose opposite corner follows the pointer. We wait for `BAND_SELECT_THRESHOLD` of pointer
+    // travel before actually treating it as a band, rather than a click, so a steady-handed click
+    // never paints or selects anything.
+    if mosaic_response.drag_started() {
+        mosaic_memory.band_start = ui.input().pointer.interact_pos().map(|p| p - mosaic_rect.min.to_vec2());
+    }
+
+    let band_rect = mosaic_memory.band_start.zip(ui.input().pointer.interact_pos()).and_then(|(start, current)| {
+        let current = current - mosaic_rect.min.to_vec2();
+        // egui reports `dragged()` false on the release frame, so also accept `drag_released()`
+        // there; otherwise the commit below would never see a band.
+        ((mosaic_response.dragged() || mosaic_response.drag_released())
+            && (current - start).length() > BAND_SELECT_THRESHOLD)
+            .then(|| Rect::from_two_pos(start, current))
+    });
+
+    if let Some(band_rect) = band_rect {
+        ui.painter().add(Shape::rect_filled(band_rect, 0., BAND_SELECT_FILL));
+        ui.painter().add(Shape::rect_stroke(band_rect, 0., Stroke::new(1., BAND_SELECT_STROKE)));
+    }
 
     // Apply focus, selection, drag, and animation
+
+    // Translucent preview of each dragged tile, painted on a tooltip-order layer so it floats
+    // above the mosaic and follows the cursor instead of only mutating tiles' layout offsets.
+    let mut drag_ghosts: Vec<(Rect, TextureId)> = Vec::new();
+
     insertion_point.clone_from(&Default::default());
     let mut tiles: Vec<Tile> = tiles.into_iter().map(|TileInMosaic {tile, output_insertion_point}| {
         let focused = match mosaic_memory.focused {
@@ -531,8 +681,16 @@ pub fn mosaic_ui<IdSource>(
         let selected = mosaic_memory.selected.contains(&tile.id().node);
 
         let (dragging, drag_offset) = mosaic_memory.drag.as_ref().and_then(|drag|
-            drag.contingent.contains(&tile.id())
-            .then(|| (true, drag.offset - (tile.rect().min - Pos2::ZERO))) // XXX tile.rect().min should be target_tile.min
+            drag.contingent.contains(&tile.id()).then(|| {
+                let offset = match drag.cancelling {
+                    Some((from, start)) => {
+                        let alpha = ease((ui.input().time - start) as f32 / DRAG_CANCEL_DURATION);
+                        from * (1. - alpha)
+                    },
+                    None => drag.offset,
+                };
+                (true, offset)
+            })
         ).unwrap_or((false, Vec2::ZERO));
 
         if focused {
@@ -547,6 +705,11 @@ pub fn mosaic_ui<IdSource>(
         }
         let tile = tile.with_focus(focused).with_selected(selected).with_offset(drag_offset);
         let tile = mosaic_memory.animation_manager.animate_tile(&ui.input(), tile, dragging);
+        if dragging {
+            if let Some(&preview_image) = preview_images.get(&tile.id().node) {
+                drag_ghosts.push((tile.rect().translate(tile.offset()), preview_image));
+            }
+        }
         tile
     }).collect();
 
@@ -557,6 +720,36 @@ pub fn mosaic_ui<IdSource>(
     let tile_ids = tiles.iter().map(|tile| tile.ui_id()).collect();
     mosaic_memory.animation_manager.retain_tiles(&tile_ids);
 
+    // Every tile id present in this frame's layout, so a dragged node's connected component can
+    // be filtered down to tiles that actually exist (e.g. not ones pruned by `props.graph.fix()`).
+    let all_tile_ids: Vec<TileId> = tiles.iter().map(|tile| tile.id()).collect();
+
+    // Advance (and eventually finish) an in-progress drag cancellation. Keep repainting every
+    // frame so the snap-back animation is visible even with no further input.
+    let is_cancelling = match &mut mosaic_memory.drag {
+        Some(DragMemory { cancelling: Some((_, start)), .. }) => {
+            if (ui.input().time - *start) as f32 >= DRAG_CANCEL_DURATION {
+                mosaic_memory.drag = None;
+                events.push(MosaicEvent::DragEnd);
+            } else {
+                ui.ctx().request_repaint();
+            }
+            true
+        },
+        _ => false,
+    };
+
+    // Paint the drag ghosts on their own tooltip-order layer, above the mosaic and everything
+    // else drawn into `ui`, mirroring the drag-source pattern used elsewhere in egui UIs.
+    if !drag_ghosts.is_empty() {
+        let ghost_painter = ui.ctx().layer_painter(LayerId::new(Order::Tooltip, mosaic_id.with("drag_ghost")));
+        let uv = Rect::from_min_max(pos2(0., 0.), pos2(1., 1.));
+        for (rect, preview_image) in drag_ghosts {
+            ghost_painter.add(Shape::rect_filled(rect, 4., DRAG_GHOST_BACKDROP));
+            ghost_painter.add(Shape::image(preview_image, rect, uv, DRAG_GHOST_TINT));
+        }
+    }
+
     // Draw
 
     // Set this variable when iterating over tiles to describe the drag situation
@@ -569,6 +762,9 @@ pub fn mosaic_ui<IdSource>(
 
     let mut drag_situation = DragSituation::None;
 
+    // Nodes whose tile rect intersects the rubber-band this frame, if one is in progress.
+    let mut band_hits: HashSet<NodeId> = HashSet::new();
+
     for tile in tiles.into_iter() {
         let tile_id = tile.id();
         let tile_rect = tile.rect();
@@ -577,18 +773,18 @@ pub fn mosaic_ui<IdSource>(
         let &preview_image = preview_images.get(&node_id).unwrap();
         let node_props = props.node_props.get_mut(&node_id).unwrap();
 
+        if let Some(band_rect) = band_rect {
+            if band_rect.intersects(tile_rect) {
+                band_hits.insert(node_id);
+            }
+        }
+
         let InnerResponse { inner, response } = tile.show(ui, |ui| {
             match node_props {
                 NodeProps::EffectNode(p) => EffectNodeTile::new(p, node_state.try_into().unwrap(), preview_image).add_contents(ui),
             }
         });
 
-        if response.drag_released() {
-            if mosaic_memory.drag.is_some() {
-                drag_situation = DragSituation::Released;
-            }
-        }
-
         // How we need to change selection based on interaction
         enum SelectionAction {
             None, // Do not select this tile
@@ -598,29 +794,51 @@ pub fn mosaic_ui<IdSource>(
 
         let mut selection_action = SelectionAction::None;
 
-        if response.dragged() {
+        if response.drag_started() {
+            // Start accumulating displacement for this press; it's not a drag yet.
+            mosaic_memory.press = Some((tile_id, Vec2::ZERO));
+            events.push(MosaicEvent::Pressed(tile_id));
+        }
+
+        // While a cancelled drag is easing back to its origin, suppress new selection/drag
+        // actions entirely; the tiles involved aren't in a settled state to interact with yet.
+        if response.dragged() && !is_cancelling {
             let delta = response.drag_delta();
-            match &mosaic_memory.drag {
-                Some(_) => {
-                    // We have an existing drag. Apply our delta.
-                    drag_situation = DragSituation::Delta(delta);
-                },
-                None => {
-                    // See if we have moved a nonzero amount. If so, begin the drag.
-                    if delta != Vec2::ZERO {
-                        // Workaround bug in egui: Discard the first delta,
-                        // since it can be inaccurate when mixing touch + mouse
-                        let offset = tile_rect.min - Pos2::ZERO;
-                        drag_situation = DragSituation::Started(tile_id, offset);
+            if mosaic_memory.drag.is_some() {
+                // We have an existing drag. Apply our delta.
+                drag_situation = DragSituation::Delta(delta);
+            } else if let Some((press_tile, accumulated)) = mosaic_memory.press.as_mut() {
+                if *press_tile == tile_id {
+                    *accumulated += delta;
+                    // Only promote the press to a drag once it has moved far enough that it's
+                    // unlikely to have been a click; below this, it stays a pending press so a
+                    // touch/mouse jitter on button-down can't masquerade as a drag.
+                    if accumulated.length() > DRAG_START_THRESHOLD {
+                        // Seed the drag's offset with the displacement already accumulated
+                        // while it was a pending press, so the tile doesn't snap back to zero
+                        // (and then lag the cursor by that much for the rest of the drag) the
+                        // moment it's promoted past the threshold.
+                        drag_situation = DragSituation::Started(tile_id, *accumulated);
                         // Treat starting a drag like a click,
                         // but ensure the tile is selected
                         // (so we never drag a deselected tile)
                         selection_action = SelectionAction::ClickedEnsureSelected;
                     }
-                },
+                }
+            }
+        }
+
+        if response.drag_released() {
+            if mosaic_memory.drag.is_some() && !is_cancelling {
+                drag_situation = DragSituation::Released;
+            } else if matches!(drag_situation, DragSituation::None) && !is_cancelling {
+                // The press never crossed the drag threshold: resolve it as a click.
+                selection_action = SelectionAction::Clicked;
+                events.push(MosaicEvent::Clicked(tile_id));
+            }
+            if mosaic_memory.press.as_ref().map_or(false, |&(press_tile, _)| press_tile == tile_id) {
+                mosaic_memory.press = None;
             }
-        } else if response.clicked() && matches!(drag_situation, DragSituation::None) {
-            selection_action = SelectionAction::Clicked;
         }
 
         match selection_action {
@@ -655,23 +873,49 @@ pub fn mosaic_ui<IdSource>(
 
     match drag_situation {
         DragSituation::Started(tile_id, offset) => {
-            let contingent: HashSet<TileId> = [tile_id].into_iter().collect(); // XXX calculate connected component
+            let contingent: HashSet<TileId> = if ui.input().modifiers.alt {
+                // Alt restricts the drag to just the grabbed tile, so a single node can still be
+                // pulled out of a chain instead of dragging its whole connected subgraph along.
+                [tile_id].into_iter().collect()
+            } else {
+                let reachable = connected_component(tile_id.node, props);
+                all_tile_ids.iter().copied().filter(|id| reachable.contains(&id.node)).collect()
+            };
+            events.push(MosaicEvent::DragStart { target: tile_id, contingent: contingent.clone() });
             mosaic_memory.drag = Some(DragMemory {
                 target: tile_id,
                 contingent,
                 offset,
+                cancelling: None,
             });
         },
         DragSituation::Delta(delta) => {
             let drag = mosaic_memory.drag.as_mut().unwrap(); // Don't emit this drag situation if None
             drag.offset += delta;
+            events.push(MosaicEvent::Drag(delta));
         },
         DragSituation::Released => {
+            // A real drop: commit it (here, simply releasing the tiles back to their laid-out
+            // positions) rather than easing back through the cancel animation.
             mosaic_memory.drag = None;
+            events.push(MosaicEvent::Drop);
+            events.push(MosaicEvent::DragEnd);
         },
         DragSituation::None => {},
     };
 
+    // Commit the rubber-band selection once the background press is released.
+    if mosaic_response.drag_released() && band_rect.is_some() {
+        if ui.input().modifiers.ctrl {
+            mosaic_memory.selected.extend(band_hits);
+        } else {
+            mosaic_memory.selected = band_hits;
+        }
+    }
+    if !mosaic_response.dragged() {
+        mosaic_memory.band_start = None;
+    }
+
     // Check if background was clicked, and if so, blur, deselect, and drop tiles
     if mosaic_response.clicked() {
         // Focus the mosaic
@@ -689,12 +933,26 @@ pub fn mosaic_ui<IdSource>(
             },
         }
 
-        // Drop tiles if they are lifted
-        mosaic_memory.drag = None;
+        // A background click mid-drag didn't land on anything meaningful; cancel rather than
+        // drop, so the dragged tiles ease back to their origin instead of vanishing in place.
+        if let Some(drag) = mosaic_memory.drag.as_mut() {
+            if begin_drag_cancel(drag, ui.input().time) {
+                events.push(MosaicEvent::Cancel);
+            }
+        }
     }
 
     // Graph interactions
     if mosaic_response.has_focus() {
+        // Handle escape key: cancel an in-progress drag with a snap-back animation
+        if ui.input().key_pressed(egui::Key::Escape) {
+            if let Some(drag) = mosaic_memory.drag.as_mut() {
+                if begin_drag_cancel(drag, ui.input().time) {
+                    events.push(MosaicEvent::Cancel);
+                }
+            }
+        }
+
         // Handle scroll wheel
         let intensity_delta = ui.input().scroll_delta.y * INTENSITY_SCROLL_RATE;
         if intensity_delta != 0. {
@@ -716,24 +974,17 @@ pub fn mosaic_ui<IdSource>(
         }
     }
 
-    mosaic_response
-}
+    // Reflect the drag phase in the cursor so the interaction reads clearly even before the
+    // ghost layer has visibly displaced anything.
+    if mosaic_memory.drag.is_some() && !is_cancelling {
+        ui.output().cursor_icon = CursorIcon::Grabbing;
+    } else if mosaic_memory.press.is_some() {
+        ui.output().cursor_icon = CursorIcon::Grab;
+    }
 
-pub fn mosaic<'a, IdSource>(
-    id_source: IdSource,
-    props: &'a mut Props,
-    node_states: &'a HashMap<NodeId, NodeState>,
-    preview_images: &'a HashMap<NodeId, TextureId>,
-    insertion_point: &'a mut InsertionPoint,
-) -> impl Widget + 'a
-    where IdSource: Hash + std::fmt::Debug + 'a,
-{
-    move |ui: &mut Ui| mosaic_ui(
-        ui,
-        id_source,
-        props,
-        node_states,
-        preview_images,
-        insertion_point,
-    )
+    if mosaic_memory.selected != selected_before {
+        events.push(MosaicEvent::SelectionChanged(mosaic_memory.selected.clone()));
+    }
+
+    (mosaic_response, events)
 }
@@ -12,8 +12,59 @@ use egui_winit::winit::{
 use std::sync::Arc;
 use std::iter;
 use std::collections::HashMap;
+use std::path::Path;
 use serde_json::json;
 
+/// Errors from [`WinitOutput::capture`]/[`WinitOutput::save_png`].
+#[derive(Debug)]
+pub enum CaptureError {
+    /// There's no `ScreenOutput` (and so no render target) for this node id.
+    UnknownNode(radiance::NodeId),
+    /// The readback buffer's `map_async` resolved to an error.
+    MapFailed,
+    /// `save_png`'s image encode step failed.
+    Encode(String),
+}
+
+impl std::fmt::Display for CaptureError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CaptureError::UnknownNode(node_id) => write!(f, "no ScreenOutput for node {:?}", node_id),
+            CaptureError::MapFailed => write!(f, "readback buffer map_async failed"),
+            CaptureError::Encode(details) => write!(f, "PNG encode failed: {}", details),
+        }
+    }
+}
+
+impl std::error::Error for CaptureError {}
+
+pub type CaptureResult<T> = std::result::Result<T, CaptureError>;
+
+/// Whether the native window is available to create a surface against yet. Always `true`
+/// outside Android, where the window exists for the process's whole lifetime; on Android the
+/// window is created lazily by the OS and is only actually ready once `ndk_glue` has it.
+#[cfg(target_os = "android")]
+fn native_window_ready() -> bool {
+    ndk_glue::native_window().is_some()
+}
+
+#[cfg(not(target_os = "android"))]
+fn native_window_ready() -> bool {
+    true
+}
+
+/// Parses a `ScreenOutputNodeProps::present_mode` value back into a `wgpu::PresentMode`,
+/// matched against `{:?}`'s spelling (`"Fifo"`/`"Mailbox"`/`"Immediate"`) since that's also
+/// how `available_present_modes` below names them for the props to round-trip through.
+fn parse_present_mode(name: &str) -> Option<wgpu::PresentMode> {
+    match name {
+        "Fifo" => Some(wgpu::PresentMode::Fifo),
+        "Mailbox" => Some(wgpu::PresentMode::Mailbox),
+        "Immediate" => Some(wgpu::PresentMode::Immediate),
+        _ => None,
+    }
+}
+
 #[derive(Debug)]
 pub struct WinitOutput {
     instance: Arc<wgpu::Instance>,
@@ -26,6 +77,14 @@ pub struct WinitOutput {
 
     screen_outputs: HashMap<radiance::NodeId, ScreenOutput>,
     available_screens: HashMap<String, (PhysicalPosition<i32>, PhysicalSize<u32>)>,
+
+    // How many frames' worth of per-frame output bind groups to keep alive at once: a slot
+    // being reused drops the bind group from `frames_in_flight` frames ago rather than the
+    // one from last frame, giving the GPU slack to have actually finished with it instead of
+    // the CPU stalling on it.
+    frames_in_flight: usize,
+    frame_index: u64,
+    bind_group_ring: Vec<Vec<wgpu::BindGroup>>,
 }
 
 #[derive(Debug)]
@@ -35,14 +94,29 @@ struct ScreenOutput {
 
     // Resources
     window: egui_winit::winit::window::Window,
-    surface: wgpu::Surface,
+    // `None` whenever there's no native window to present to: before the native window is
+    // available on Android, or between a `Suspended`/`Resumed` pair on Android (where
+    // backgrounding the app destroys it). `config` tracks the requested size regardless, so
+    // there's a configuration ready to reconfigure with once `resume()` rebuilds the surface.
+    surface: Option<wgpu::Surface>,
     config: wgpu::SurfaceConfiguration,
-    render_pipeline: wgpu::RenderPipeline,
+    // Paired with `surface`: rebuilt alongside it, since its target format comes from
+    // whatever `surface.get_supported_formats()` reports, which isn't known ahead of having
+    // an actual surface to ask.
+    render_pipeline: Option<wgpu::RenderPipeline>,
     render_target_id: radiance::RenderTargetId,
     render_target: radiance::RenderTarget,
 
     // Internal
     initial_update: bool, // Initialized to false, set to true on first update.
+    // The `(screen_name, position, size)` this window was last placed at; re-diffed every
+    // `update()` against `available_screens` so a hotplugged monitor's new resolution or
+    // position gets re-applied instead of only ever being set once on the `newly_visible` edge.
+    applied_placement: Option<(String, PhysicalPosition<i32>, PhysicalSize<u32>)>,
+    // Set when `update()` hides this output because its target screen disappeared, so that
+    // visibility can be restored automatically once the screen comes back, rather than
+    // leaving it hidden until the user notices and re-toggles it.
+    auto_hidden: bool,
 }
 
 impl ScreenOutput {
@@ -50,9 +124,19 @@ impl ScreenOutput {
         if new_size.width > 0 && new_size.height > 0 {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
-            self.surface.configure(device, &self.config);
+            if let Some(surface) = &self.surface {
+                surface.configure(device, &self.config);
+            }
         }
     }
+
+    /// Drops the surface and its paired pipeline; the native window (and so the surface)
+    /// doesn't survive the app being backgrounded on Android. Painting is skipped while
+    /// `surface` is `None`.
+    fn suspend(&mut self) {
+        self.surface = None;
+        self.render_pipeline = None;
+    }
 }
 
 impl WinitOutput {
@@ -93,6 +177,8 @@ impl WinitOutput {
             }
         );
 
+        const DEFAULT_FRAMES_IN_FLIGHT: usize = 2;
+
         WinitOutput {
             instance,
             adapter,
@@ -103,9 +189,21 @@ impl WinitOutput {
             render_pipeline_layout,
             screen_outputs: HashMap::<radiance::NodeId, ScreenOutput>::new(),
             available_screens: HashMap::<String, (PhysicalPosition<i32>, PhysicalSize<u32>)>::new(),
+            frames_in_flight: DEFAULT_FRAMES_IN_FLIGHT,
+            frame_index: 0,
+            bind_group_ring: vec![Vec::new(); DEFAULT_FRAMES_IN_FLIGHT],
         }
     }
 
+    /// Changes how many frames' worth of output bind groups are kept in the ring (see the
+    /// comment on the `frames_in_flight` field); resets the ring, so call this before `render_all`
+    /// has painted anything meaningful, not mid-session.
+    pub fn set_frames_in_flight(&mut self, frames_in_flight: usize) {
+        self.frames_in_flight = frames_in_flight.max(1);
+        self.bind_group_ring = vec![Vec::new(); self.frames_in_flight];
+        self.frame_index = 0;
+    }
+
     pub fn render_targets_iter(&self) -> impl Iterator<Item=(&radiance::RenderTargetId, &radiance::RenderTarget)> {
         self.screen_outputs.values().map(|screen_output| (&screen_output.render_target_id, &screen_output.render_target))
     }
@@ -156,18 +254,40 @@ impl WinitOutput {
 
             // Populate each screen output node props with a list of screens available on the system
             screen_output_props.available_screens = screen_names.clone();
+
             if !self.available_screens.contains_key(&screen_output_props.screen) {
-                // Hide any outputs that point to screens we don't know about
+                // Hide any outputs that point to screens we don't know about, remembering
+                // that we're the ones who hid it so visibility can come back on its own once
+                // the screen reappears.
+                if screen_output_props.visible {
+                    screen_output.auto_hidden = true;
+                }
                 screen_output_props.visible = false;
+            } else if screen_output.auto_hidden {
+                screen_output_props.visible = true;
+                screen_output.auto_hidden = false;
             }
 
             // Cache props and act on them
             let newly_visible = !screen_output.visible && screen_output_props.visible;
             screen_output.visible = screen_output_props.visible;
             screen_output.window.set_visible(screen_output.visible);
-            if newly_visible {
-                println!("NEWLY VISIBLE!!");
-                let &(target_screen_position, target_screen_size) = self.available_screens.get(&screen_output_props.screen).unwrap();
+
+            if !screen_output.visible {
+                screen_output.applied_placement = None;
+                continue;
+            }
+
+            let Some(&(target_screen_position, target_screen_size)) = self.available_screens.get(&screen_output_props.screen) else {
+                continue;
+            };
+            let placement = (screen_output_props.screen.clone(), target_screen_position, target_screen_size);
+
+            // Re-diff against the last-applied placement on every update, not just on the
+            // `newly_visible` edge, so a hotplugged monitor's new resolution/position (or a
+            // previously-missing screen reappearing) gets re-applied without requiring the
+            // user to toggle `visible` off and back on.
+            if newly_visible || screen_output.applied_placement.as_ref() != Some(&placement) {
                 screen_output.window.set_resizable(false);
                 screen_output.window.set_decorations(false);
 
@@ -189,39 +309,116 @@ impl WinitOutput {
                 screen_output.window.set_inner_size(target_screen_size);
                 screen_output.window.set_outer_position(target_screen_position);
                 screen_output.window.set_fullscreen(Some(Fullscreen::Borderless(None)));
+                screen_output.resize(&self.device, target_screen_size);
+                screen_output.render_target.width = target_screen_size.width;
+                screen_output.render_target.height = target_screen_size.height;
                 println!("Move to: {:?}", target_screen_position);
+
+                screen_output.applied_placement = Some(placement);
+            }
+
+            // Surface the adapter's actually-supported present modes into the props (the
+            // same way `available_screens` is populated above), so a UI can only offer
+            // valid choices, then reconfigure live if the user's selection (or preferred
+            // format) changed instead of only ever picking one at creation time.
+            if let Some(surface) = &screen_output.surface {
+                let supported_present_modes = surface.get_supported_present_modes(&self.adapter);
+                screen_output_props.available_present_modes = supported_present_modes
+                    .iter()
+                    .map(|mode| format!("{:?}", mode))
+                    .collect();
+
+                let preferred_present_mode = parse_present_mode(&screen_output_props.present_mode)
+                    .unwrap_or(wgpu::PresentMode::Fifo);
+                let resolved_present_mode = supported_present_modes
+                    .iter()
+                    .copied()
+                    .find(|mode| *mode == preferred_present_mode)
+                    // Fifo is required by the wgpu spec to always be supported.
+                    .unwrap_or(wgpu::PresentMode::Fifo);
+
+                let supported_formats = surface.get_supported_formats(&self.adapter);
+                let resolved_format = screen_output_props
+                    .preferred_format
+                    .as_ref()
+                    .and_then(|name| supported_formats.iter().copied().find(|format| format!("{:?}", format) == *name))
+                    .unwrap_or(supported_formats[0]);
+
+                let format_changed = resolved_format != screen_output.config.format;
+                if resolved_present_mode != screen_output.config.present_mode || format_changed {
+                    screen_output.config.present_mode = resolved_present_mode;
+                    screen_output.config.format = resolved_format;
+                    surface.configure(&self.device, &screen_output.config);
+                    if format_changed {
+                        screen_output.render_pipeline = Some(Self::build_render_pipeline(
+                            &self.device,
+                            &self.shader_module,
+                            &self.render_pipeline_layout,
+                            resolved_format,
+                        ));
+                    }
+                }
             }
         }
     }
 
-    fn new_screen_output<T>(&self, event_loop: &EventLoopWindowTarget<T>) -> ScreenOutput {
-        let window = WindowBuilder::new().build(&event_loop).unwrap();
-        let size = window.inner_size();
-        let surface = unsafe { self.instance.create_surface(&window) };
+    /// Builds a surface, its matching config (queried off the surface's own supported
+    /// formats), and the render pipeline targeting that format, all together since they're
+    /// only ever valid as a set: created fresh in `new_screen_output` when the native window
+    /// is already available, or later in `resume()` once it is. A free function (rather than
+    /// a `&self` method) so `resume()` can call it while `self.screen_outputs` is mutably
+    /// borrowed by its iteration.
+    fn build_surface_resources(
+        instance: &wgpu::Instance,
+        adapter: &wgpu::Adapter,
+        device: &wgpu::Device,
+        shader_module: &wgpu::ShaderModule,
+        render_pipeline_layout: &wgpu::PipelineLayout,
+        window: &egui_winit::winit::window::Window,
+        width: u32,
+        height: u32,
+    ) -> (wgpu::Surface, wgpu::SurfaceConfiguration, wgpu::RenderPipeline) {
+        let surface = unsafe { instance.create_surface(window) };
 
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface.get_supported_formats(&self.adapter)[0],
-            width: size.width,
-            height: size.height,
+            format: surface.get_supported_formats(adapter)[0],
+            width,
+            height,
             present_mode: wgpu::PresentMode::Fifo,
             alpha_mode: wgpu::CompositeAlphaMode::Auto,
         };
-        surface.configure(&self.device, &config);
+        surface.configure(device, &config);
 
-        let render_pipeline = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let render_pipeline =
+            Self::build_render_pipeline(device, shader_module, render_pipeline_layout, config.format);
+
+        (surface, config, render_pipeline)
+    }
+
+    /// The blit pipeline targeting a given swapchain format. Split out of
+    /// `build_surface_resources` so reconfiguring just the format (e.g. a user-selected
+    /// `preferred_format` in `update()`) can rebuild the pipeline without tearing down and
+    /// recreating the surface itself.
+    fn build_render_pipeline(
+        device: &wgpu::Device,
+        shader_module: &wgpu::ShaderModule,
+        render_pipeline_layout: &wgpu::PipelineLayout,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::RenderPipeline {
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Output Render Pipeline"),
-            layout: Some(&self.render_pipeline_layout),
+            layout: Some(render_pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &self.shader_module,
+                module: shader_module,
                 entry_point: "vs_main",
                 buffers: &[],
             },
             fragment: Some(wgpu::FragmentState {
-                module: &self.shader_module,
+                module: shader_module,
                 entry_point: "fs_main",
                 targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
+                    format,
                     blend: Some(wgpu::BlendState::REPLACE),
                     write_mask: wgpu::ColorWrites::ALL,
                 })],
@@ -242,7 +439,42 @@ impl WinitOutput {
                 alpha_to_coverage_enabled: false,
             },
             multiview: None,
-        });
+        })
+    }
+
+    fn new_screen_output<T>(&self, event_loop: &EventLoopWindowTarget<T>) -> ScreenOutput {
+        let window = WindowBuilder::new().build(&event_loop).unwrap();
+        let size = window.inner_size();
+
+        // On Android the native window doesn't exist until the app is actually resumed, and
+        // `instance.create_surface` panics without one; defer surface creation to `resume()`
+        // in that case (a no-op everywhere else, since the native window is available from
+        // the start on desktop).
+        let (surface, config, render_pipeline) = if native_window_ready() {
+            let (surface, config, render_pipeline) = Self::build_surface_resources(
+                &self.instance,
+                &self.adapter,
+                &self.device,
+                &self.shader_module,
+                &self.render_pipeline_layout,
+                &window,
+                size.width,
+                size.height,
+            );
+            (Some(surface), config, Some(render_pipeline))
+        } else {
+            let config = wgpu::SurfaceConfiguration {
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                // Placeholder until `resume()` rebuilds against the real surface's reported
+                // format; nothing paints through this until then.
+                format: wgpu::TextureFormat::Bgra8UnormSrgb,
+                width: size.width,
+                height: size.height,
+                present_mode: wgpu::PresentMode::Fifo,
+                alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            };
+            (None, config, None)
+        };
 
         let render_target_id = radiance::RenderTargetId::gen();
         let render_target: radiance::RenderTarget = serde_json::from_value(json!({
@@ -260,79 +492,169 @@ impl WinitOutput {
             render_target_id,
             render_target,
             initial_update: false,
+            applied_placement: None,
+            auto_hidden: false,
         }
     }
 
-    pub fn on_event<T>(&mut self, event: &Event<T>, ctx: &mut radiance::Context) -> bool {
+    /// Rebuilds any screen output's surface that `Event::Suspended` dropped (or that never
+    /// got one because the native window wasn't ready yet), now that the native window is
+    /// back. A no-op for any screen output that already has a surface.
+    fn resume(&mut self) {
+        for screen_output in self.screen_outputs.values_mut() {
+            if screen_output.surface.is_some() {
+                continue;
+            }
+            let size = screen_output.window.inner_size();
+            let (surface, config, render_pipeline) = Self::build_surface_resources(
+                &self.instance,
+                &self.adapter,
+                &self.device,
+                &self.shader_module,
+                &self.render_pipeline_layout,
+                &screen_output.window,
+                size.width,
+                size.height,
+            );
+            screen_output.surface = Some(surface);
+            screen_output.config = config;
+            screen_output.render_pipeline = Some(render_pipeline);
+        }
+    }
+
+    /// Paints every visible, ready `ScreenOutput` in one batched pass instead of each
+    /// window's own `RedrawRequested` recording and submitting independently: one `ctx.paint`
+    /// per distinct `render_target_id` (screens that happened to share a render target would
+    /// only get painted once), every window's blit recorded into a single shared
+    /// `CommandEncoder`, and one final `queue.submit`. Called once per `MainEventsCleared`.
+    fn render_all(&mut self, ctx: &mut radiance::Context) {
+        let ready: Vec<radiance::NodeId> = self
+            .screen_outputs
+            .iter()
+            .filter(|(_, screen_output)| {
+                screen_output.initial_update
+                    && screen_output.visible
+                    && screen_output.surface.is_some()
+                    && screen_output.render_pipeline.is_some()
+            })
+            .map(|(node_id, _)| *node_id)
+            .collect();
+
+        if ready.is_empty() {
+            return;
+        }
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Output Encoder"),
+        });
+
+        let mut results_by_target = HashMap::new();
+        for node_id in &ready {
+            let render_target_id = self.screen_outputs[node_id].render_target_id;
+            results_by_target
+                .entry(render_target_id)
+                .or_insert_with(|| ctx.paint(&mut encoder, render_target_id));
+        }
+
+        // Drop the bind groups from `frames_in_flight` frames ago, not last frame's, so a
+        // pending submission still referencing them has had a full ring's worth of frames to
+        // actually finish on the GPU.
+        let slot = self.frame_index as usize % self.frames_in_flight;
+        self.bind_group_ring[slot].clear();
+
+        let mut presents = Vec::with_capacity(ready.len());
+        for node_id in &ready {
+            let screen_output = &self.screen_outputs[node_id];
+            let results = &results_by_target[&screen_output.render_target_id];
+            let Some(texture) = results.get(node_id) else {
+                continue;
+            };
+
+            let output_bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&texture.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&texture.sampler),
+                    },
+                ],
+                label: Some("output bind group"),
+            });
+
+            let surface = screen_output.surface.as_ref().unwrap();
+            let render_pipeline = screen_output.render_pipeline.as_ref().unwrap();
+            let output = surface.get_current_texture().unwrap();
+            let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+            {
+                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: Some("Output window render pass"),
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color {
+                                r: 0.,
+                                g: 0.,
+                                b: 0.,
+                                a: 0.,
+                            }),
+                            store: true,
+                        },
+                    })],
+                    depth_stencil_attachment: None,
+                });
+
+                render_pass.set_pipeline(render_pipeline);
+                render_pass.set_bind_group(0, &output_bind_group, &[]);
+                render_pass.draw(0..4, 0..1);
+            }
+
+            self.bind_group_ring[slot].push(output_bind_group);
+            presents.push(output);
+        }
+
+        // One submit for every window painted this frame, instead of one per window.
+        self.queue.submit(iter::once(encoder.finish()));
+
+        for output in presents {
+            output.present();
+        }
+
+        self.frame_index = self.frame_index.wrapping_add(1);
+    }
+
+    pub fn on_event<T>(&mut self, event: &Event<T>, _event_loop: &EventLoopWindowTarget<T>, ctx: &mut radiance::Context) -> bool {
         // Return true => event consumed
         // Return false => event continues to be processed
 
-        for (node_id, screen_output) in self.screen_outputs.iter_mut() {
+        // Handled up front (rather than in the per-screen_output loop below) since rebuilding
+        // a surface needs `&self.instance`/`&self.device`/etc alongside `&mut self.screen_outputs`.
+        if let Event::Resumed = event {
+            self.resume();
+        }
+
+        // All visible screens are painted together right here, in one batched encoder/submit
+        // (see `render_all`'s doc comment), rather than each window's own `RedrawRequested`
+        // paying for its own. `request_redraw` below still drives a `RedrawRequested` per
+        // window afterward, but by then there's nothing left to do except present, which
+        // `render_all` already did.
+        if let Event::MainEventsCleared = event {
+            self.render_all(ctx);
+        }
+
+        for (_node_id, screen_output) in self.screen_outputs.iter_mut() {
             match event {
                 Event::RedrawRequested(window_id) if window_id == &screen_output.window.id() => {
-                    if screen_output.initial_update && screen_output.visible {
-                        // Paint
-                        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                            label: Some("Output Encoder"),
-                        });
-
-                        let results = ctx.paint(&mut encoder, screen_output.render_target_id);
-
-                        if let Some(texture) = results.get(&node_id) {
-                            let output_bind_group = self.device.create_bind_group(
-                                &wgpu::BindGroupDescriptor {
-                                    layout: &self.bind_group_layout,
-                                    entries: &[
-                                        wgpu::BindGroupEntry {
-                                            binding: 0,
-                                            resource: wgpu::BindingResource::TextureView(&texture.view),
-                                        },
-                                        wgpu::BindGroupEntry {
-                                            binding: 1,
-                                            resource: wgpu::BindingResource::Sampler(&texture.sampler),
-                                        }
-                                    ],
-                                    label: Some("output bind group"),
-                                }
-                            );
-
-                            // Record output render pass.
-                            let output = screen_output.surface.get_current_texture().unwrap();
-                            let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-                            {
-                                let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                                    label: Some("Output window render pass"),
-                                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                                        view: &view,
-                                        resolve_target: None,
-                                        ops: wgpu::Operations {
-                                            load: wgpu::LoadOp::Clear(wgpu::Color {
-                                                r: 0.,
-                                                g: 0.,
-                                                b: 0.,
-                                                a: 0.,
-                                            }),
-                                            store: true,
-                                        },
-                                    })],
-                                    depth_stencil_attachment: None,
-                                });
-
-                                render_pass.set_pipeline(&screen_output.render_pipeline);
-                                render_pass.set_bind_group(0, &output_bind_group, &[]);
-                                render_pass.draw(0..4, 0..1);
-                            }
-
-                            // Submit the commands.
-                            self.queue.submit(iter::once(encoder.finish()));
-
-                            // Draw
-                            output.present();
-                        }
-                    }
                     return true;
                 }
+                Event::Suspended => {
+                    screen_output.suspend();
+                }
                 Event::WindowEvent {
                     ref event,
                     window_id,
@@ -358,4 +680,95 @@ impl WinitOutput {
         }
         false
     }
+
+    /// Renders `node_id`'s `render_target` and reads it back to tightly-packed RGBA8 bytes,
+    /// independent of whatever's on screen: its own encoder, submit, and `map_async` +
+    /// `device.poll()` round-trip, rather than piggybacking on a `RedrawRequested` paint.
+    /// Standalone so an offscreen recorder can later drive the same path without a window.
+    pub fn capture(&self, ctx: &mut radiance::Context, node_id: radiance::NodeId) -> CaptureResult<(u32, u32, Vec<u8>)> {
+        let screen_output = self
+            .screen_outputs
+            .get(&node_id)
+            .ok_or(CaptureError::UnknownNode(node_id))?;
+        let width = screen_output.render_target.width;
+        let height = screen_output.render_target.height;
+
+        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Capture Encoder"),
+        });
+
+        let results = ctx.paint(&mut encoder, screen_output.render_target_id);
+        let texture = &results
+            .get(&node_id)
+            .ok_or(CaptureError::UnknownNode(node_id))?
+            .texture;
+
+        // `copy_texture_to_buffer` requires each row be a multiple of
+        // `COPY_BYTES_PER_ROW_ALIGNMENT` (256 bytes); pad rather than assume `width * 4` is
+        // already aligned.
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let unpadded_bytes_per_row = width * 4;
+        let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+
+        let readback_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Capture readback buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &readback_buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        self.queue.submit(iter::once(encoder.finish()));
+
+        let slice = readback_buffer.slice(..);
+        let (map_tx, map_rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = map_tx.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+
+        match map_rx.recv() {
+            Ok(Ok(())) => {
+                let padded = slice.get_mapped_range();
+                let unpadded_bytes_per_row = unpadded_bytes_per_row as usize;
+                let mut rgba = Vec::with_capacity(unpadded_bytes_per_row * height as usize);
+                for row in padded.chunks(padded_bytes_per_row as usize) {
+                    rgba.extend_from_slice(&row[..unpadded_bytes_per_row]);
+                }
+                drop(padded);
+                readback_buffer.unmap();
+                Ok((width, height, rgba))
+            }
+            _ => Err(CaptureError::MapFailed),
+        }
+    }
+
+    /// Convenience wrapper around [`capture`](Self::capture) that encodes straight to a PNG
+    /// file, for quick still-capture of live output.
+    pub fn save_png(&self, ctx: &mut radiance::Context, node_id: radiance::NodeId, path: &Path) -> CaptureResult<()> {
+        let (width, height, rgba) = self.capture(ctx, node_id)?;
+        image::save_buffer(path, &rgba, width, height, image::ColorType::Rgba8)
+            .map_err(|e| CaptureError::Encode(e.to_string()))
+    }
 }
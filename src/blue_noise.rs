@@ -0,0 +1,237 @@
+// A tileable blue-noise texture generator via the void-and-cluster algorithm (Ulichney 1993):
+// start from a small random binary pattern, relax it into a "balanced" prototype whose points
+// are evenly spread (no denser or sparser anywhere than the toroidal Gaussian energy says they
+// should be), then rank every pixel by the order it would be added to (or removed from) that
+// prototype. Thresholding the resulting rank image at any level yields an evenly spaced subset,
+// which is exactly the property blue noise needs for dithering/stippling to look uniform
+// instead of clumpy, the way per-pixel white noise does.
+
+use rand::Rng;
+
+/// Which kind of noise `DefaultChain`'s noise texture should hold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseKind {
+    /// Per-pixel independent random bytes. Cheap, but clumpy: some regions end up denser than
+    /// others purely by chance, which reads as visible clustering when used for dithering.
+    White,
+    /// Void-and-cluster blue noise: expensive to generate, but every region has (almost)
+    /// exactly the same density, so thresholding it gives evenly spaced samples at any level.
+    Blue,
+}
+
+const SIGMA: f32 = 1.5;
+
+/// Precomputed `(dx, dy, weight)` offsets for the Gaussian kernel, out to the radius past which
+/// its contribution rounds to negligible.
+fn gaussian_kernel() -> Vec<(i32, i32, f32)> {
+    let radius = (3. * SIGMA).ceil() as i32;
+    let mut kernel = Vec::new();
+    for dy in -radius..=radius {
+        for dx in -radius..=radius {
+            let r2 = (dx * dx + dy * dy) as f32;
+            kernel.push((dx, dy, (-r2 / (2. * SIGMA * SIGMA)).exp()));
+        }
+    }
+    kernel
+}
+
+/// Tracks the Gaussian-filtered energy every "on" pixel of a binary pattern contributes to
+/// every other pixel, on a toroidal (wraparound) grid so the result tiles seamlessly.
+/// Maintained incrementally (`update_point`) rather than recomputed from scratch so relaxing a
+/// pattern of `n` pixels costs `O(n)` per point moved rather than `O(n)`-per-pixel-per-move.
+struct EnergyGrid {
+    width: i32,
+    height: i32,
+    energy: Vec<f32>,
+    kernel: Vec<(i32, i32, f32)>,
+}
+
+impl EnergyGrid {
+    fn new(width: i32, height: i32) -> Self {
+        EnergyGrid {
+            width,
+            height,
+            energy: vec![0.; (width * height) as usize],
+            kernel: gaussian_kernel(),
+        }
+    }
+
+    fn index(&self, x: i32, y: i32) -> usize {
+        (y.rem_euclid(self.height) * self.width + x.rem_euclid(self.width)) as usize
+    }
+
+    /// Adds (`sign = 1.`) or removes (`sign = -1.`) one point's Gaussian contribution.
+    fn update_point(&mut self, index: usize, sign: f32) {
+        let x = index as i32 % self.width;
+        let y = index as i32 / self.width;
+        for &(dx, dy, weight) in &self.kernel {
+            let idx = self.index(x + dx, y + dy);
+            self.energy[idx] += sign * weight;
+        }
+    }
+
+    /// The "on" pixel with the highest energy, i.e. the one most tightly packed with its
+    /// neighbors.
+    fn tightest_cluster(&self, binary: &[bool]) -> usize {
+        (0..binary.len())
+            .filter(|&i| binary[i])
+            .max_by(|&a, &b| self.energy[a].partial_cmp(&self.energy[b]).unwrap())
+            .expect("tightest_cluster called on an all-zero pattern")
+    }
+
+    /// The "off" pixel with the lowest energy, i.e. the one furthest from any "on" neighbor.
+    fn largest_void(&self, binary: &[bool]) -> usize {
+        (0..binary.len())
+            .filter(|&i| !binary[i])
+            .min_by(|&a, &b| self.energy[a].partial_cmp(&self.energy[b]).unwrap())
+            .expect("largest_void called on an all-ones pattern")
+    }
+}
+
+/// Relaxes `binary` (mutated in place) into a balanced prototype: repeatedly find the tightest
+/// cluster and largest void, and relocate the cluster's point into the void, until a pass finds
+/// that the point's own former position is itself the largest void, i.e. moving it elsewhere
+/// wouldn't improve the spread any further.
+fn relax_initial_pattern(binary: &mut [bool], grid: &mut EnergyGrid) {
+    loop {
+        let cluster = grid.tightest_cluster(binary);
+        grid.update_point(cluster, -1.);
+        binary[cluster] = false;
+
+        let void = grid.largest_void(binary);
+        if void == cluster {
+            grid.update_point(cluster, 1.);
+            binary[cluster] = true;
+            break;
+        }
+
+        grid.update_point(void, 1.);
+        binary[void] = true;
+    }
+}
+
+/// Ranks every pixel of a relaxed binary pattern by the order it would join (or leave) it:
+/// counting down from the initial pattern's point count by repeatedly removing the tightest
+/// cluster (phase 1), then counting up from there by repeatedly filling the largest void, all
+/// the way through the rest of the grid (phases 2 and 3 of the classic algorithm collapse into
+/// one loop here, since "keep filling voids" is the same operation on either side of the
+/// halfway point).
+fn rank_pattern(binary: &[bool], grid: &mut EnergyGrid) -> Vec<u32> {
+    let n = binary.len();
+    let initial_ones = binary.iter().filter(|&&b| b).count();
+    let mut ranks = vec![0_u32; n];
+
+    let mut working = binary.to_vec();
+    for rank in (0..initial_ones).rev() {
+        let cluster = grid.tightest_cluster(&working);
+        grid.update_point(cluster, -1.);
+        working[cluster] = false;
+        ranks[cluster] = rank as u32;
+    }
+
+    for i in 0..n {
+        if binary[i] {
+            grid.update_point(i, 1.);
+        }
+    }
+    let mut working = binary.to_vec();
+    for rank in initial_ones..n {
+        let void = grid.largest_void(&working);
+        grid.update_point(void, 1.);
+        working[void] = true;
+        ranks[void] = rank as u32;
+    }
+
+    ranks
+}
+
+/// Generates a `width`x`height` tileable blue-noise pattern as one byte per pixel (the pixel's
+/// rank, normalized to `0..=255`). `O(n^2)` in the pixel count, so this is meant to run once
+/// (on a background `WorkerPool` task, per `DefaultContext::add_chain_with_noise`) rather than
+/// per frame.
+pub fn generate_blue_noise(width: u32, height: u32) -> Vec<u8> {
+    let w = width as i32;
+    let h = height as i32;
+    let n = (w * h) as usize;
+
+    let mut grid = EnergyGrid::new(w, h);
+    let mut binary = vec![false; n];
+
+    // Seed with ~10% of pixels on, the conventional starting density for the relaxation phase:
+    // sparse enough that the initial pattern's spacing is meaningful, dense enough to converge
+    // quickly.
+    let mut rng = rand::thread_rng();
+    let initial_count = (n / 10).max(1);
+    let mut placed = 0;
+    while placed < initial_count {
+        let index = rng.gen_range(0..n);
+        if !binary[index] {
+            binary[index] = true;
+            grid.update_point(index, 1.);
+            placed += 1;
+        }
+    }
+
+    relax_initial_pattern(&mut binary, &mut grid);
+    let ranks = rank_pattern(&binary, &mut grid);
+
+    ranks.iter().map(|&rank| ((rank as f32 / (n - 1) as f32) * 255.).round() as u8).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_blue_noise_size_and_range() {
+        let noise = generate_blue_noise(8, 8);
+        assert_eq!(noise.len(), 64);
+        assert!(noise.iter().any(|&b| b < 64));
+        assert!(noise.iter().any(|&b| b > 192));
+    }
+
+    #[test]
+    fn test_generate_blue_noise_covers_every_rank_exactly_once() {
+        // Every pixel gets a distinct rank in 0..n, so the normalized bytes come from a
+        // permutation: no value should be over-represented the way per-pixel white noise's
+        // binomial distribution would produce.
+        let noise = generate_blue_noise(6, 6);
+        let mut sorted = noise.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        // With 36 pixels mapped into 0..=255 the ranks are 255/35 (~7.3) apart, so rounding
+        // can't merge any two of them: every byte value is distinct.
+        assert_eq!(sorted.len(), noise.len(), "{} of {} byte values distinct", sorted.len(), noise.len());
+    }
+
+    #[test]
+    fn test_relax_initial_pattern_reduces_energy_variance() {
+        // A maximally clumped pattern (all points in one corner) should relax into something
+        // whose per-point energy is far more uniform than it started.
+        let (w, h) = (10, 10);
+        let mut grid = EnergyGrid::new(w, h);
+        let mut binary = vec![false; (w * h) as usize];
+        // A 5x2 block in the top-left corner. (A full row would wrap around the torus and give
+        // every point the same energy, leaving no variance to reduce.)
+        for i in 0..10 {
+            let index = (i / 5) * w as usize + i % 5;
+            binary[index] = true;
+            grid.update_point(index, 1.);
+        }
+
+        let energies_before: Vec<f32> = (0..100).filter(|&i| binary[i]).map(|i| grid.energy[i]).collect();
+        let variance_before = variance(&energies_before);
+
+        relax_initial_pattern(&mut binary, &mut grid);
+
+        let energies_after: Vec<f32> = (0..100).filter(|&i| binary[i]).map(|i| grid.energy[i]).collect();
+        let variance_after = variance(&energies_after);
+
+        assert!(variance_after < variance_before, "{} !< {}", variance_after, variance_before);
+    }
+
+    fn variance(values: &[f32]) -> f32 {
+        let mean = values.iter().sum::<f32>() / values.len() as f32;
+        values.iter().map(|v| (v - mean).powi(2)).sum::<f32>() / values.len() as f32
+    }
+}
@@ -1,96 +1,387 @@
-use crate::types::{NoiseTexture, BlankTexture, GraphicsContext, Texture, WorkerPool, Graphics, FetchContent};
+use crate::types::{NoiseTexture, BlankTexture, GraphicsContext, Texture, WorkerPool, WorkHandle, WorkResult, Graphics, FetchContent, FetchTexture, ShaderCacheAccess, UniformAllocator, Timebase, Audio};
 use crate::threaded_worker::ThreadWorkHandle;
+use crate::uniform_arena::UniformArena;
+use crate::effect_node::{EffectNode, EffectNodePaintState};
+use crate::media_node::DecodedImage;
+use crate::shader_cache::ShaderCache;
+use crate::blue_noise::{self, NoiseKind};
+use crate::texture_builder;
+use crate::mir::Mir;
 use wgpu;
 use std::rc::Rc;
+use std::cell::RefCell;
+use std::fmt;
+use std::time::Instant;
 use rand;
 use std::collections::HashMap;
 use std::fs::read_to_string;
+use image;
+
+// Total size of the shared uniform arena: generous enough for thousands of `EffectNode`s'
+// `UpdateUniforms`/`PaintUniforms` slots (each rounded up to a ~256 byte alignment) without
+// ever needing to grow the backing buffer out from under an existing bind group.
+const UNIFORM_ARENA_CAPACITY: wgpu::BufferAddress = 1 << 20;
+
+// Used until a tempo is tapped in, matching the common "start at 120" convention of most DAWs
+// and VJ tools.
+const DEFAULT_BPM: f32 = 120.;
+
+// A wall-clock delta longer than this (the window was minimized, a breakpoint was hit, ...) is
+// clamped instead of being fed through verbatim, so a stall doesn't look like one enormous
+// beat-phase jump to every listening EffectNode.
+const MAX_FRAME_DELTA: f32 = 0.25;
+
+// Two taps further apart than this don't look like the same tempo being tapped in; treat the
+// later one as the start of a fresh tapping sequence instead of implying a sub-30-BPM tempo.
+const MAX_TAP_INTERVAL: f32 = 2.;
+
+// Where compiled shader artifacts are cached on disk, relative to the working directory,
+// when the `shader-cache` feature is enabled.
+const SHADER_CACHE_DIR: &str = "shader_cache";
+
+/// A handle to an `EffectNode` owned by a `DefaultContext`'s graph, returned by `add_node` and
+/// passed to `connect`/`node`/`node_mut`. Distinct from the library's richer `NodeId` (which
+/// addresses a `Props`-based scene graph); this one only makes sense within a single
+/// `DefaultContext`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphNodeId(u32);
 
-#[derive(Debug)]
 pub struct DefaultContext {
     chains: HashMap<u32, DefaultChain>,
     chain_id: u32,
     graphics: Rc<GraphicsContext>,
     blank_texture: Rc<Texture>,
+    uniform_arena: RefCell<UniformArena>,
+    last_frame: Instant,
+    time: f32,
+    bpm: f32,
+    beat_phase: f32,
+    last_tap: Option<Instant>,
+    nodes: HashMap<GraphNodeId, EffectNode<DefaultContext>>,
+    node_id: u32,
+    // (to, slot) -> from. A node's input slots mirror its shader's `#property inputCount`.
+    edges: HashMap<(GraphNodeId, u32), GraphNodeId>,
+    shader_cache: ShaderCache,
+    // Blue-noise patterns already generated for a given chain size, so adding several chains of
+    // the same resolution only pays the O(n^2) void-and-cluster cost once.
+    blue_noise_cache: HashMap<(u32, u32), Rc<Vec<u8>>>,
+    // Chains whose blue-noise pattern is still generating on a background WorkerPool task;
+    // polled (and applied once finished) by `update()`. The chain itself already shows white
+    // noise in the meantime, from `DefaultChain::new`.
+    generating_noise: Vec<GeneratingNoise>,
+    // Listens to the system audio and derives smoothed low/mid/high/level envelopes from it;
+    // polled once per `update()` so every `EffectNode`'s `iAudio` sees this frame's values
+    // rather than re-polling (and re-smoothing) per node.
+    mir: Mir,
+    audio_bands: [f32; 4],
+}
+
+// Manual impl since `Mir` holds a `cpal::Stream`/`mpsc::Receiver`, neither of which is `Debug`.
+impl fmt::Debug for DefaultContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DefaultContext")
+            .field("chains", &self.chains)
+            .field("chain_id", &self.chain_id)
+            .field("time", &self.time)
+            .field("bpm", &self.bpm)
+            .field("beat_phase", &self.beat_phase)
+            .field("nodes", &self.nodes)
+            .field("edges", &self.edges)
+            .field("blue_noise_cache", &self.blue_noise_cache)
+            .field("generating_noise", &self.generating_noise)
+            .field("audio_bands", &self.audio_bands)
+            .finish_non_exhaustive()
+    }
+}
+
+/// One chain's in-flight background blue-noise generation.
+struct GeneratingNoise {
+    chain_id: u32,
+    size: (u32, u32),
+    handle: ThreadWorkHandle<Vec<u8>>,
+}
+
+impl fmt::Debug for GeneratingNoise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeneratingNoise")
+            .field("chain_id", &self.chain_id)
+            .field("size", &self.size)
+            .finish()
+    }
 }
 
 impl DefaultContext {
     pub fn new(graphics: Rc<GraphicsContext>) -> DefaultContext {
         let tex = DefaultContext::create_blank_texture(&graphics);
+        let uniform_arena = UniformArena::new(&graphics.device, "effect uniform arena", UNIFORM_ARENA_CAPACITY);
 
         DefaultContext {
             chains: HashMap::new(),
             graphics: graphics,
             blank_texture: tex,
+            uniform_arena: RefCell::new(uniform_arena),
             chain_id: 0,
+            last_frame: Instant::now(),
+            time: 0.,
+            bpm: DEFAULT_BPM,
+            beat_phase: 0.,
+            last_tap: None,
+            nodes: HashMap::new(),
+            node_id: 0,
+            edges: HashMap::new(),
+            shader_cache: ShaderCache::new(SHADER_CACHE_DIR),
+            blue_noise_cache: HashMap::new(),
+            generating_noise: Vec::new(),
+            mir: Mir::new(),
+            audio_bands: [0.; 4],
         }
     }
 
-    fn create_blank_texture(graphics: &GraphicsContext) -> Rc<Texture> {
-        // Create blank texture
-        let texture_size = wgpu::Extent3d {
-            width: 1,
-            height: 1,
-            depth: 1,
-        };
-        let texture = graphics.device.create_texture(
-            &wgpu::TextureDescriptor {
-                size: texture_size,
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
-                label: Some("blank_texture"),
+    /// Deletes every cached shader artifact (see `ShaderCache`), for invalidating the cache
+    /// after a naga/shaderc version bump or other change that would make cached bytes unsafe
+    /// to reuse. With the `shader-cache` feature disabled an existing cache directory is still
+    /// removed, but no empty one is recreated.
+    pub fn clear_cache(&self) -> std::io::Result<()> {
+        self.shader_cache.clear_cache()
+    }
+
+    /// Advances the shared clock by the wall-clock time elapsed since the last call,
+    /// accumulating `time` and wrapping `beat_phase` into `[0, 1)` at the current `bpm`. Call
+    /// this once per frame, before updating any `EffectNode`s that read it.
+    pub fn update(&mut self) {
+        let now = Instant::now();
+        let dt = (now - self.last_frame).as_secs_f32().min(MAX_FRAME_DELTA);
+        self.last_frame = now;
+
+        self.time += dt;
+        let beats_per_sec = self.bpm / 60.;
+        self.beat_phase = (self.beat_phase + dt * beats_per_sec).rem_euclid(1.);
+
+        let info = self.mir.poll();
+        self.audio_bands = [info.low, info.mid, info.high, info.level];
+
+        self.poll_generating_noise();
+    }
+
+    /// Checks every chain's in-flight blue-noise generation, uploads whichever finished this
+    /// frame (caching the result per size so a later chain of the same resolution skips
+    /// straight to it), and leaves the rest generating.
+    fn poll_generating_noise(&mut self) {
+        let mut i = 0;
+        while i < self.generating_noise.len() {
+            if self.generating_noise[i].handle.alive() {
+                i += 1;
+                continue;
             }
-        );
 
-        graphics.queue.write_texture(
-            wgpu::TextureCopyView {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-            },
-            &[0, 0, 0, 0],
-            wgpu::TextureDataLayout {
-                offset: 0,
-                bytes_per_row: 4,
-                rows_per_image: 1,
-            },
-            texture_size,
-        );
+            let GeneratingNoise { chain_id, size, handle } = self.generating_noise.remove(i);
+            if let WorkResult::Ok(ranks) = handle.join() {
+                // Broadcast the single-channel rank value across R, G, B, and A, so any channel
+                // (or the RGB luminance) an effect samples for dithering sees the same
+                // well-spread pattern.
+                let rgba: Vec<u8> = ranks.into_iter().flat_map(|v| [v, v, v, v]).collect();
+                let rgba = Rc::new(rgba);
+                if let Some(chain) = self.chains.get_mut(&chain_id) {
+                    chain.set_noise_bytes(&self.graphics, &rgba);
+                }
+                self.blue_noise_cache.insert(size, rgba);
+            }
+            // A panicked generation just leaves the chain showing its white-noise placeholder.
+        }
+    }
+
+    /// Sets the BPM driving `beat_phase`, e.g. from a UI slider.
+    pub fn set_bpm(&mut self, bpm: f32) {
+        self.bpm = bpm;
+    }
+
+    pub fn bpm(&self) -> f32 {
+        self.bpm
+    }
 
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = graphics.device.create_sampler(
-            &wgpu::SamplerDescriptor {
-                address_mode_u: wgpu::AddressMode::ClampToEdge,
-                address_mode_v: wgpu::AddressMode::ClampToEdge,
-                address_mode_w: wgpu::AddressMode::ClampToEdge,
-                mag_filter: wgpu::FilterMode::Nearest,
-                min_filter: wgpu::FilterMode::Nearest,
-                mipmap_filter: wgpu::FilterMode::Nearest,
-                ..Default::default()
+    /// "Tap tempo": call this once per beat a performer taps along to (typically bound to a
+    /// key), and the BPM implied by the gap since the previous tap is adopted, the same way a
+    /// DJ controller's tap-tempo button works. The phase is reset to 0 on each accepted tap so
+    /// the downbeat lines up with the performer's tapping instead of wherever it happened to be.
+    pub fn tap_tempo(&mut self) {
+        let now = Instant::now();
+        if let Some(last_tap) = self.last_tap {
+            let interval = (now - last_tap).as_secs_f32();
+            if interval > 0. && interval < MAX_TAP_INTERVAL {
+                self.bpm = 60. / interval;
+                self.beat_phase = 0.;
             }
-        );
+        }
+        self.last_tap = Some(now);
+    }
 
-        Rc::new(Texture {
-            texture: texture,
-            view: view,
-            sampler: sampler,
-        })
+    fn create_blank_texture(graphics: &GraphicsContext) -> Rc<Texture> {
+        // A single pixel, so mipmapping would be pointless; every sample reads the same value
+        // regardless of minification.
+        texture_builder::build_texture(
+            graphics,
+            texture_builder::TextureOptions {
+                size: (1, 1),
+                mipmapped: false,
+                filter: wgpu::FilterMode::Nearest,
+                label: "blank_texture",
+            },
+            &[0, 0, 0, 0],
+        )
     }
 
     pub fn add_chain(&mut self, size: (u32, u32)) -> u32 {
+        self.add_chain_with_noise(size, NoiseKind::White)
+    }
+
+    /// Adds a new chain, as `add_chain`, but lets the caller pick its noise texture's kind.
+    /// `NoiseKind::Blue` shows the usual white-noise texture for the first few frames while the
+    /// (much more expensive) void-and-cluster pattern generates on a background `WorkerPool`
+    /// task, then swaps it in once ready; a size already generated for an earlier chain is
+    /// reused from `blue_noise_cache` immediately instead of regenerating it.
+    pub fn add_chain_with_noise(&mut self, size: (u32, u32), noise_kind: NoiseKind) -> u32 {
         let chain = DefaultChain::new(self.graphics.as_ref(), size, self.blank_texture.clone());
         let id = self.chain_id;
         self.chain_id += 1;
         self.chains.insert(id, chain);
+
+        if noise_kind == NoiseKind::Blue {
+            match self.blue_noise_cache.get(&size) {
+                Some(cached) => {
+                    let cached = cached.clone();
+                    self.chains.get_mut(&id).unwrap().set_noise_bytes(&self.graphics, &cached);
+                }
+                None => {
+                    let handle = self.spawn(move || blue_noise::generate_blue_noise(size.0, size.1));
+                    self.generating_noise.push(GeneratingNoise { chain_id: id, size, handle });
+                }
+            }
+        }
+
         id
     }
 
     pub fn chain(&self, id: u32) -> Option<&DefaultChain> {
         self.chains.get(&id)
     }
+
+    /// Adds a new, empty `EffectNode` to this context's graph and returns a handle to it. Give
+    /// it a shader with `EffectNode::set_name` (or however the caller already configures one),
+    /// wire it up to others with `connect`, then drive the whole graph each frame with
+    /// `update_and_paint_graph`.
+    pub fn add_node(&mut self) -> GraphNodeId {
+        let id = GraphNodeId(self.node_id);
+        self.node_id += 1;
+        self.nodes.insert(id, EffectNode::new());
+        id
+    }
+
+    pub fn node(&self, id: GraphNodeId) -> Option<&EffectNode<DefaultContext>> {
+        self.nodes.get(&id)
+    }
+
+    pub fn node_mut(&mut self, id: GraphNodeId) -> Option<&mut EffectNode<DefaultContext>> {
+        self.nodes.get_mut(&id)
+    }
+
+    /// Wires `from`'s output texture into `to`'s `slot`-th input (the same slot space as its
+    /// shader's `#property inputCount`, i.e. `iChannel0`/`iChannel1`/...), replacing whatever
+    /// was connected to that slot before.
+    pub fn connect(&mut self, from: GraphNodeId, to: GraphNodeId, slot: u32) {
+        self.edges.insert((to, slot), from);
+    }
+
+    pub fn disconnect(&mut self, to: GraphNodeId, slot: u32) {
+        self.edges.remove(&(to, slot));
+    }
+
+    /// Kahn's algorithm over `edges`, so `update_and_paint_graph` can visit every node only
+    /// after all of its inputs, instead of relying on `nodes`' arbitrary hash-map order.
+    fn topo_order(&self) -> Vec<GraphNodeId> {
+        let mut in_degree: HashMap<GraphNodeId, usize> = self.nodes.keys().map(|&id| (id, 0)).collect();
+        let mut outgoing: HashMap<GraphNodeId, Vec<GraphNodeId>> = HashMap::new();
+        for (&(to, _slot), &from) in &self.edges {
+            *in_degree.entry(to).or_insert(0) += 1;
+            outgoing.entry(from).or_default().push(to);
+        }
+
+        let mut ready: Vec<GraphNodeId> = in_degree.iter().filter(|(_, &d)| d == 0).map(|(&id, _)| id).collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(id) = ready.pop() {
+            order.push(id);
+            if let Some(outs) = outgoing.get(&id) {
+                for &next in outs {
+                    let degree = in_degree.get_mut(&next).unwrap();
+                    *degree -= 1;
+                    if *degree == 0 {
+                        ready.push(next);
+                    }
+                }
+            }
+        }
+
+        // A cycle would leave some nodes permanently blocked; `connect` doesn't reject one
+        // today, so fall back to appending whatever's left in an arbitrary order rather than
+        // silently dropping nodes from the frame.
+        if order.len() < self.nodes.len() {
+            for &id in self.nodes.keys() {
+                if !order.contains(&id) {
+                    order.push(id);
+                }
+            }
+        }
+        order
+    }
+
+    /// Advances every node in the graph and paints it against `chain_id`, in dependency order,
+    /// so a node downstream of another always sees that node's fresh output for this frame
+    /// rather than last frame's. `paint_states` holds one `EffectNodePaintState` per node
+    /// (built the same way as the single-node path, via `EffectNode::new_paint_state`); a node
+    /// with no entry is updated but not painted. Returns the command buffers for every node's
+    /// paint, meant to be submitted together in one `queue.submit` call, alongside each painted
+    /// node's output texture for this frame.
+    pub fn update_and_paint_graph(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        chain_id: u32,
+        paint_states: &mut HashMap<GraphNodeId, EffectNodePaintState>,
+    ) -> (Vec<wgpu::CommandBuffer>, HashMap<GraphNodeId, Rc<Texture>>) {
+        let order = self.topo_order();
+        let mut cmds = Vec::new();
+        let mut outputs: HashMap<GraphNodeId, Rc<Texture>> = HashMap::new();
+
+        for id in order {
+            // Pull the node out of `self.nodes` so it can be handed `self` as its own
+            // `UpdateContext`/`PaintContext` without aliasing the map it just came from.
+            let mut node = match self.nodes.remove(&id) {
+                Some(node) => node,
+                None => continue,
+            };
+
+            node.update(self, device, queue);
+
+            if let Some(paint_state) = paint_states.get_mut(&id) {
+                let chain = self.chain(chain_id).expect("update_and_paint_graph: unknown chain_id");
+                let n_inputs = self.edges.keys()
+                    .filter(|&&(to, _)| to == id)
+                    .map(|&(_, slot)| slot + 1)
+                    .max()
+                    .unwrap_or(0);
+                let inputs: Vec<Option<Rc<Texture>>> = (0..n_inputs)
+                    .map(|slot| self.edges.get(&(id, slot)).and_then(|from| outputs.get(from).cloned()))
+                    .collect();
+
+                let (node_cmds, tex) = node.paint(chain, device, queue, paint_state, &inputs);
+                cmds.extend(node_cmds);
+                outputs.insert(id, tex);
+            }
+
+            self.nodes.insert(id, node);
+        }
+
+        (cmds, outputs)
+    }
 }
 
 #[derive(Debug)]
@@ -103,63 +394,45 @@ pub struct DefaultChain {
 impl DefaultChain {
     /// Construct a new chain for a given texture size
     pub fn new(graphics: &GraphicsContext, size: (u32, u32), blank_texture: Rc<Texture>) -> DefaultChain {
-        let texture_size = wgpu::Extent3d {
-            width: size.0,
-            height: size.1,
-            depth: 1,
-        };
-        let texture = graphics.device.create_texture(
-            &wgpu::TextureDescriptor {
-                size: texture_size,
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST | wgpu::TextureUsage::COPY_SRC, // XXX remove COPY_SRC
-                label: Some("noise texture"),
-            }
-        );
-
         let random_bytes: Vec<u8> = (0 .. size.0 * size.1 * 4).map(|_| { rand::random::<u8>() }).collect();
 
-        graphics.queue.write_texture(
-            wgpu::TextureCopyView {
-                texture: &texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
+        // Mipmapped, since the noise is tiled and sampled at all sorts of on-screen scales by
+        // the effects that use it; a single level left minification aliasing to the sampler's
+        // (mip-less) linear filter alone, which couldn't actually help.
+        let noise_texture = texture_builder::build_texture(
+            graphics,
+            texture_builder::TextureOptions {
+                size,
+                mipmapped: true,
+                filter: wgpu::FilterMode::Linear,
+                label: "noise texture",
             },
             &random_bytes,
-            wgpu::TextureDataLayout {
-                offset: 0,
-                bytes_per_row: 4 * size.0,
-                rows_per_image: size.1,
-            },
-            texture_size,
-        );
-
-        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = graphics.device.create_sampler(
-            &wgpu::SamplerDescriptor {
-                address_mode_u: wgpu::AddressMode::ClampToEdge,
-                address_mode_v: wgpu::AddressMode::ClampToEdge,
-                address_mode_w: wgpu::AddressMode::ClampToEdge,
-                mag_filter: wgpu::FilterMode::Linear,
-                min_filter: wgpu::FilterMode::Linear,
-                mipmap_filter: wgpu::FilterMode::Nearest,
-                ..Default::default()
-            }
         );
 
         DefaultChain {
             size: size,
-            noise_texture: Rc::new(Texture {
-                texture: texture,
-                view: view,
-                sampler: sampler,
-            }),
+            noise_texture: noise_texture,
             blank_texture: blank_texture,
         }
     }
+
+    /// Replaces the noise texture with a freshly built one holding `rgba` (one `Rgba8UnormSrgb`
+    /// byte quadruple per pixel, `self.size.0 * self.size.1` of them) and its generated mip
+    /// chain. Used to swap a chain's placeholder white-noise texture for its void-and-cluster
+    /// blue-noise texture once generation finishes on a background `WorkerPool` task.
+    fn set_noise_bytes(&mut self, graphics: &GraphicsContext, rgba: &[u8]) {
+        self.noise_texture = texture_builder::build_texture(
+            graphics,
+            texture_builder::TextureOptions {
+                size: self.size,
+                mipmapped: true,
+                filter: wgpu::FilterMode::Linear,
+                label: "noise texture",
+            },
+            rgba,
+        );
+    }
 }
 
 impl BlankTexture for DefaultChain {
@@ -188,6 +461,28 @@ impl Graphics for DefaultContext {
     }
 }
 
+impl UniformAllocator for DefaultContext {
+    fn uniform_arena(&self) -> &RefCell<UniformArena> {
+        &self.uniform_arena
+    }
+}
+
+impl Timebase for DefaultContext {
+    fn time(&self) -> f32 {
+        self.time
+    }
+
+    fn beat_phase(&self) -> f32 {
+        self.beat_phase
+    }
+}
+
+impl Audio for DefaultContext {
+    fn audio_bands(&self) -> [f32; 4] {
+        self.audio_bands
+    }
+}
+
 impl FetchContent for DefaultContext {
     fn fetch_content_closure(&self, name: &str) -> Box<dyn FnOnce() -> std::io::Result<String> + Send + 'static> {
         let cloned_name = name.to_string();
@@ -195,4 +490,37 @@ impl FetchContent for DefaultContext {
             read_to_string(cloned_name)
         })
     }
+
+    // Unlike fetch_content_closure(), this isn't bound to a single name up front:
+    // the shader preprocessor doesn't know the paths of a shader's #include'd files
+    // until it's partway through parsing it, so it needs a fetcher it can call
+    // by name as many times as the include tree is deep.
+    fn fetch_content_closure_any(&self) -> Box<dyn Fn(&str) -> std::io::Result<String> + Send + 'static> {
+        Box::new(|name| read_to_string(name))
+    }
+}
+
+impl ShaderCacheAccess for DefaultContext {
+    fn shader_cache_get_closure(&self) -> Box<dyn Fn(&str) -> Option<Vec<u8>> + Send + 'static> {
+        let cache = self.shader_cache.clone();
+        Box::new(move |key| cache.get(key))
+    }
+
+    fn shader_cache_put_closure(&self) -> Box<dyn Fn(&str, Vec<u8>) + Send + 'static> {
+        let cache = self.shader_cache.clone();
+        Box::new(move |key, bytes| cache.put(key, &bytes))
+    }
+}
+
+impl FetchTexture for DefaultContext {
+    fn fetch_texture_closure(&self, name: &str) -> Box<dyn FnOnce() -> Result<DecodedImage, String> + Send + 'static> {
+        let cloned_name = name.to_string();
+        Box::new(move || {
+            let decoded = image::open(&cloned_name)
+                .map_err(|e| format!("failed to decode {}: {}", cloned_name, e))?
+                .to_rgba8();
+            let (width, height) = decoded.dimensions();
+            Ok(DecodedImage { width, height, rgba: decoded.into_raw() })
+        })
+    }
 }
@@ -1,4 +1,6 @@
-use crate::types::{Texture, BlankTexture, NoiseTexture, WorkerPool, WorkHandle, WorkResult, FetchContent, Resolution, Timebase};
+use crate::types::{Texture, BlankTexture, NoiseTexture, WorkerPool, WorkHandle, WorkResult, FetchContent, Resolution, Timebase, Audio, UniformAllocator, ShaderCacheAccess};
+use crate::shader_preprocessor;
+use crate::shader_cache::ShaderCache;
 use std::rc::Rc;
 use shaderc;
 use std::fmt;
@@ -12,43 +14,158 @@ use bytemuck;
 #[derive(Debug)]
 pub struct EffectNodePaintState {
     input_textures: Vec<Rc<Texture>>,
+    // One intermediate texture per non-final pass, so a later pass can sample any earlier
+    // pass's result (ping-ponged between these rather than reusing a single pair, since a
+    // pass may need to sample further back than just its immediate predecessor). The final
+    // pass renders straight into `output_texture` instead of needing a slot here.
+    pass_textures: Vec<Rc<Texture>>,
+    // Whether `pass_textures` (and `output_texture` via its own flag below) are currently the
+    // storage-texture variant a compute effect needs, rather than the render-attachment
+    // variant a fragment effect needs, and what format they were allocated with. Tracked so a
+    // hot-reload that flips `#kind` or `#property format` rebuilds them instead of binding the
+    // wrong kind (or format) of texture next frame.
+    pass_textures_are_storage: bool,
+    pass_textures_format: wgpu::TextureFormat,
     output_texture: Rc<Texture>,
+    output_is_storage: bool,
+    output_format: wgpu::TextureFormat,
+    // Previous frames' finished `output_texture`s, most recent first (`channel_textures[0]`
+    // is last frame, `channel_textures[1]` two frames ago, etc). Unlike `pass_textures` this
+    // persists across `paint()` calls rather than within one: `paint()` swaps the buffers in
+    // at the end of each call instead of recreating them, so an effect can read its own
+    // recent history for trails/decay/accumulation. Starts empty and grows up to
+    // `ReadyState::max_channels` over the first few frames as history accumulates.
+    channel_textures: Vec<Rc<Texture>>,
 }
 
 /// The EffectNode struct contains context-specific, chain-agnostic data.
 /// It is constructed by calling new()
 #[derive(Debug)]
-pub struct EffectNode<UpdateContext: WorkerPool + FetchContent + Timebase> {
+pub struct EffectNode<UpdateContext: WorkerPool + FetchContent + Timebase + Audio + UniformAllocator + ShaderCacheAccess> {
     pending: EffectNodePendingChanges,
     state: EffectNodeState<UpdateContext>,
     name: Option<String>,
     intensity: f32,
+    // Set by notify_file_changed() when the file backing `name` is edited on disk;
+    // consumed (and cleared) the next time update() runs, which recompiles in place.
+    needs_recompile: bool,
+    // The error from the most recent failed compile, if any, kept around even after
+    // falling back to `state: Ready(..)` so the UI can surface it next to the node
+    // without the live pipeline having to go dark while the edit is fixed.
+    last_error: Option<String>,
+    // The fully `#include`/`#define`-expanded source of the most recently *successfully*
+    // compiled shader, kept around even across a failed hot-reload (same reasoning as
+    // `last_error`) so callers can cache or inspect exactly what was last handed to shaderc.
+    last_expanded_source: Option<String>,
+    // `context.time()` as of the previous `update()` call, used to derive `dt` by
+    // differencing; `None` on the very first call, when there's nothing to difference against.
+    last_update_time: Option<f32>,
+    // The most recently computed `dt`, kept around so `paint()` (which has no state of its
+    // own to difference `iTime` against) can derive `iFPS` from it.
+    last_dt: f32,
+    // Running sum of `iIntensity * dt` across every `update()` call, wrapped modulo 1024 to
+    // keep it from blowing out of `f32` precision over a long-running set.
+    intensity_integral: f32,
 }
 
-enum EffectNodeState<UpdateContext: WorkerPool + FetchContent + Timebase> {
+enum EffectNodeState<UpdateContext: WorkerPool + FetchContent + Timebase + Audio + UniformAllocator + ShaderCacheAccess> {
     Uninitialized,
     // Note: The work handle below is really not optional.
     // The Option<> is only there to allow "taking" it as soon as compilation is done.
-    Compiling {shader_compilation_work_handle: Option<<UpdateContext as WorkerPool>::Handle<Result<Vec<u8>, String>>>},
+    Compiling {
+        shader_compilation_work_handle: Option<<UpdateContext as WorkerPool>::Handle<Result<CompiledEffect, String>>>,
+        // The pipeline this compile is replacing, if this is a hot-reload of an
+        // already-Ready node. If the new shader fails, update() restores this instead
+        // of falling through to Error, so a bad edit doesn't take the node dark.
+        fallback: Option<ReadyState>,
+    },
     Ready(ReadyState),
     Error(String),
 }
 
+/// The result of parsing and compiling an effect's source: one SPIR-V artifact per `#pass`
+/// stage (a single-pass effect with no `#pass` markers just has one) plus the input count
+/// and feedback channel count read from its `#property inputCount`/`#property channelCount`
+/// lines, instead of the single binary + hardcoded `n_inputs` `setup_render_pipeline` used to
+/// work with. `kind` selects whether those SPIR-V artifacts are fragment or compute shaders,
+/// from a `#kind compute` / `#workgroup x y` header (default is `#kind render`, i.e. the usual
+/// fullscreen-triangle-strip pipeline). `format` is the effect's working texture format, from
+/// `#property format` (default `EffectFormat::Srgb8`).
+pub struct CompiledEffect {
+    input_count: u32,
+    channel_count: u32,
+    kind: EffectKind,
+    format: EffectFormat,
+    passes: Vec<Vec<u8>>,
+    // The `shader_preprocessor::preprocess` output this was compiled from, carried along so
+    // `update()` can stash it in `EffectNode::last_expanded_source` without re-running the
+    // preprocessor.
+    expanded_src: String,
+}
+
+/// Whether an effect's passes run as fragment shaders over a fullscreen triangle strip or as
+/// compute shaders dispatched over the chain's resolution. Compute effects can do neighbor
+/// gathers, histograms, and separable blurs that a per-pixel fragment shader can't express
+/// efficiently; they trade the implicit rasterizer for an explicit storage-texture output and
+/// workgroup size declared by the shader itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EffectKind {
+    Render,
+    Compute { workgroup_size: (u32, u32) },
+}
+
 // Extra state associated with an EffectNode when it is Ready
 struct ReadyState {
-    render_pipeline: wgpu::RenderPipeline,
+    // One pipeline per `#pass` stage, run in order; the last one writes
+    // `EffectNodePaintState::output_texture`, the others write `pass_textures`. Render and
+    // compute pipelines can't share a type, so both kinds coexist behind this enum rather than
+    // `ReadyState` needing two optional fields.
+    pipelines: EffectPipelines,
     update_bind_group: wgpu::BindGroup,
     paint_bind_group_layout: wgpu::BindGroupLayout,
-    update_uniform_buffer: wgpu::Buffer,
-    paint_uniform_buffer: wgpu::Buffer,
+    // Both point into the shared `UniformArena` (see `crate::uniform_arena`) rather than a
+    // buffer of this node's own: `uniform_buffer` is that arena's backing buffer (cloned, so
+    // paint() doesn't need a reference back to the context just to bind it), and the two
+    // offsets are this node's permanent slots in it, passed as dynamic offsets at draw time.
+    uniform_buffer: wgpu::Buffer,
+    update_uniform_offset: wgpu::BufferAddress,
+    paint_uniform_offset: wgpu::BufferAddress,
     n_inputs: u32,
+    // Fixed size of the paint bind group's `iPassTex[]` array: one slot per pass that could
+    // possibly precede another (`pipelines.len() - 1`, floored at 1 so a single-pass effect's
+    // layout still has a — unused — slot to bind a placeholder texture into).
+    max_prev_passes: u32,
+    // Fixed size of the paint bind group's `iChannelTex[]` array, i.e. how many frames of its
+    // own history this effect can see (from `#property channelCount`, floored at 1 for the
+    // same reason as `max_prev_passes`).
+    max_channels: u32,
+    // This effect's working texture format, from `#property format`; governs the
+    // output/intermediate texture `paint()` allocates, alongside `pipelines`' kind.
+    format: EffectFormat,
+}
+
+enum EffectPipelines {
+    Render(Vec<wgpu::RenderPipeline>),
+    Compute {
+        pipelines: Vec<wgpu::ComputePipeline>,
+        workgroup_size: (u32, u32),
+    },
 }
 
-impl<UpdateContext: WorkerPool + FetchContent + Timebase> fmt::Debug for EffectNodeState<UpdateContext> {
+impl EffectPipelines {
+    fn len(&self) -> usize {
+        match self {
+            EffectPipelines::Render(pipelines) => pipelines.len(),
+            EffectPipelines::Compute { pipelines, .. } => pipelines.len(),
+        }
+    }
+}
+
+impl<UpdateContext: WorkerPool + FetchContent + Timebase + Audio + UniformAllocator + ShaderCacheAccess> fmt::Debug for EffectNodeState<UpdateContext> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             EffectNodeState::Uninitialized => write!(f, "Uninitialized"),
-            EffectNodeState::Compiling {shader_compilation_work_handle: _} => write!(f, "Compiling"),
+            EffectNodeState::Compiling {shader_compilation_work_handle: _, fallback: _} => write!(f, "Compiling"),
             EffectNodeState::Ready(_) => write!(f, "Ready"),
             EffectNodeState::Error(e) => write!(f, "Error({})", e),
         }
@@ -85,9 +202,131 @@ struct PaintUniforms {
     iFPS: f32,
 }
 
+// wgpu storage-texture bindings can't use an sRGB format, unlike the render path's default
+// `Rgba8UnormSrgb` output/pass textures, so a compute effect working in `EffectFormat::Srgb8`
+// uses this for its storage output (and any intermediate pass feeding another compute pass)
+// instead.
+const COMPUTE_OUTPUT_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+/// An effect's working texture format, set by `#property format` and shared by every pass's
+/// output/intermediate texture. `Srgb8` (the default, unnamed historical behavior) clamps to
+/// 8-bit sRGB at every pass boundary, which crushes highlights once a few bright additive
+/// effects are chained; `Rgba16Float` keeps the whole chain in linear HDR, only converting to
+/// sRGB at the final display node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum EffectFormat {
+    Srgb8,
+    Rgba16Float,
+}
+
+impl EffectFormat {
+    fn from_property(value: &str) -> EffectFormat {
+        match value {
+            "rgba16f" => EffectFormat::Rgba16Float,
+            _ => EffectFormat::Srgb8,
+        }
+    }
+
+    /// The format for a render-pass color attachment / sampled intermediate texture.
+    fn render_format(&self) -> wgpu::TextureFormat {
+        match self {
+            EffectFormat::Srgb8 => wgpu::TextureFormat::Rgba8UnormSrgb,
+            EffectFormat::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
+        }
+    }
+
+    /// The format for a compute pass's storage-texture output. Only `Srgb8` needs to diverge
+    /// from `render_format()`, since sRGB formats aren't storage-capable in wgpu; `Rgba16Float`
+    /// was never an sRGB format and is storage-capable as-is.
+    fn storage_format(&self) -> wgpu::TextureFormat {
+        match self {
+            EffectFormat::Srgb8 => COMPUTE_OUTPUT_FORMAT,
+            EffectFormat::Rgba16Float => wgpu::TextureFormat::Rgba16Float,
+        }
+    }
+}
+
 const EFFECT_HEADER: &str = include_str!("effect_header.glsl");
+// Compute passes get their own header instead of EFFECT_HEADER: the fragment header declares
+// things (gl_FragCoord-relative helpers, the implicit fullscreen-triangle varyings) that don't
+// exist in a compute shader, and needs the local/global invocation ID plumbing instead.
+const EFFECT_COMPUTE_HEADER: &str = include_str!("effect_header_compute.glsl");
+
+/// Splits a (post-`shader_preprocessor`) effect source into its ordered `#pass` stages, and
+/// pulls `#property inputCount N`/`#property channelCount N` plus `#kind`/`#workgroup` out of
+/// the preamble. These directives are effect-specific metadata, so they're handled here rather
+/// than by the generic `#include`/`#define` preprocessor: all of them are dropped from the
+/// output entirely, and each `#pass` line starts a new stage, with anything before the first
+/// one forming the (possibly only) stage 0. A shader with no `#pass` markers at all is just a
+/// single-stage effect, same as before this was supported. `channelCount` defaults to 1
+/// (last frame's output only) rather than 0, so an effect gets feedback history for free
+/// without having to opt in. `#kind` defaults to `render` (the usual fragment-shader path);
+/// `#kind compute` switches every pass to a compute shader, and `#workgroup x y` (only
+/// meaningful alongside it) sets the dispatch tile size, defaulting to 8x8 if unspecified.
+/// `format` defaults to `EffectFormat::Srgb8` (the original hardcoded behavior); `#property
+/// format rgba16f` switches every pass's output/intermediate texture to linear HDR instead.
+fn parse_effect_source(src: &str) -> (u32, u32, EffectKind, EffectFormat, Vec<String>) {
+    let mut input_count = 1_u32;
+    let mut channel_count = 1_u32;
+    let mut is_compute = false;
+    let mut workgroup_size = (8_u32, 8_u32);
+    let mut format = EffectFormat::Srgb8;
+    let mut passes: Vec<String> = vec![String::new()];
+
+    for line in src.lines() {
+        let trimmed = line.trim_start();
+        if let Some(rest) = trimmed.strip_prefix("#property") {
+            let mut parts = rest.trim().splitn(2, char::is_whitespace);
+            match (parts.next(), parts.next()) {
+                (Some("inputCount"), Some(value)) => {
+                    if let Ok(n) = value.trim().parse::<u32>() {
+                        input_count = n;
+                    }
+                }
+                (Some("channelCount"), Some(value)) => {
+                    if let Ok(n) = value.trim().parse::<u32>() {
+                        channel_count = n;
+                    }
+                }
+                (Some("format"), Some(value)) => {
+                    format = EffectFormat::from_property(value.trim());
+                }
+                _ => {}
+            }
+            continue;
+        }
+        if let Some(rest) = trimmed.strip_prefix("#kind") {
+            is_compute = rest.trim() == "compute";
+            continue;
+        }
+        if let Some(rest) = trimmed.strip_prefix("#workgroup") {
+            let mut parts = rest.trim().splitn(2, char::is_whitespace);
+            if let (Some(x), Some(y)) = (parts.next(), parts.next()) {
+                if let (Ok(x), Ok(y)) = (x.trim().parse::<u32>(), y.trim().parse::<u32>()) {
+                    workgroup_size = (x, y);
+                }
+            }
+            continue;
+        }
+        if trimmed.starts_with("#pass") {
+            passes.push(String::new());
+            continue;
+        }
+        let current_pass = passes.last_mut().unwrap();
+        current_pass.push_str(line);
+        current_pass.push('\n');
+    }
+
+    let kind = if is_compute {
+        EffectKind::Compute { workgroup_size }
+    } else {
+        EffectKind::Render
+    };
 
-impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateContext> {
+    (input_count, channel_count, kind, format, passes)
+}
+
+impl<UpdateContext: WorkerPool + FetchContent + Timebase + Audio + UniformAllocator + ShaderCacheAccess> EffectNode<UpdateContext> {
     pub fn new() -> EffectNode<UpdateContext> {
         let pending = EffectNodePendingChanges {
             name: None,
@@ -99,33 +338,117 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
             name: pending.name.clone(),
             intensity: pending.intensity,
             pending,
+            needs_recompile: false,
+            last_error: None,
+            last_expanded_source: None,
+            last_update_time: None,
+            last_dt: 1. / 60.,
+            intensity_integral: 0.,
+        }
+    }
+
+    /// Called by whatever owns a `ShaderWatcher` when a shader file on disk has changed.
+    /// If `path` is the file backing this node, the next `update()` call will recompile it.
+    pub fn notify_file_changed(&mut self, path: &str) {
+        if self.name.as_deref() == Some(path) {
+            self.needs_recompile = true;
         }
     }
 
+    /// The error from the most recently failed compile, if the last edit didn't compile.
+    /// The pipeline this node was last successfully compiled with (if any) keeps running
+    /// regardless, so a live set doesn't go dark while this is non-`None`.
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// The fully `#include`/`#define`-expanded source of the most recently *successfully*
+    /// compiled shader. Useful for caching (skip re-preprocessing unchanged content) and for
+    /// debugging (cross-referencing a `#line`-annotated compile error against exactly what was
+    /// handed to shaderc), since `last_error`'s message alone doesn't show the flattened source.
+    pub fn last_expanded_source(&self) -> Option<&str> {
+        self.last_expanded_source.as_deref()
+    }
+
     // Called when the name changes. Sets the state to Compiling and kicks off shaderc in a worker.
     fn start_compiling_shader(&mut self, context: &UpdateContext) {
         let shader_content_closure = context.fetch_content_closure(&self.name.as_ref().unwrap());
         let shader_name = self.name.as_ref().unwrap().to_owned();
+        let fetch_include = context.fetch_content_closure_any();
+        let cache_get = context.shader_cache_get_closure();
+        let cache_put = context.shader_cache_put_closure();
 
         let shader_compilation_work_handle = context.spawn(move || {
             let effect_src = shader_content_closure().map_err(|e| e.to_string())?;
-            let frag_src = format!("{}{}\n", EFFECT_HEADER, effect_src);
+            let expanded_src = shader_preprocessor::preprocess(
+                &shader_name,
+                &effect_src,
+                |include_path| fetch_include(include_path).map_err(|e| e.to_string()),
+            )?;
+            let (input_count, channel_count, kind, format, pass_sources) = parse_effect_source(&expanded_src);
+            let shader_kind = match kind {
+                EffectKind::Render => shaderc::ShaderKind::Fragment,
+                EffectKind::Compute { .. } => shaderc::ShaderKind::Compute,
+            };
+
+            let header = match kind {
+                EffectKind::Render => EFFECT_HEADER,
+                EffectKind::Compute { .. } => EFFECT_COMPUTE_HEADER,
+            };
+
+            // Artifacts are keyed off the expanded source (which already captures every
+            // `#include`d dependency) plus pass index and stage, so a cache hit skips shaderc
+            // entirely on the common "reopened a project, nothing changed" cold start.
+            let stage_tag = match shader_kind {
+                shaderc::ShaderKind::Fragment => "fragment",
+                shaderc::ShaderKind::Compute => "compute",
+                _ => "other",
+            };
+
             let mut compiler = shaderc::Compiler::new().unwrap();
-            let compilation_result = compiler.compile_into_spirv(&frag_src, shaderc::ShaderKind::Fragment, &shader_name, "main", None);
-            match compilation_result {
-                Ok(artifact) => Ok(artifact.as_binary_u8().to_vec()),
-                Err(e) => Err(e.to_string()),
+            let mut passes = Vec::with_capacity(pass_sources.len());
+            for (pass_index, pass_src) in pass_sources.iter().enumerate() {
+                let cache_key = ShaderCache::key(&expanded_src, pass_index, stage_tag);
+                let artifact = match cache_get(&cache_key) {
+                    Some(cached) => cached,
+                    None => {
+                        let full_src = format!("{}{}\n", header, pass_src);
+                        let pass_name = format!("{} (pass {})", shader_name, pass_index);
+                        let compilation_result = compiler.compile_into_spirv(&full_src, shader_kind, &pass_name, "main", None);
+                        let bytes = match compilation_result {
+                            Ok(artifact) => artifact.as_binary_u8().to_vec(),
+                            Err(e) => return Err(e.to_string()),
+                        };
+                        cache_put(&cache_key, bytes.clone());
+                        bytes
+                    }
+                };
+                passes.push(artifact);
             }
+
+            Ok(CompiledEffect { input_count, channel_count, kind, format, passes, expanded_src })
         });
-        self.state = EffectNodeState::Compiling {shader_compilation_work_handle: Some(shader_compilation_work_handle)};
+
+        // If we're hot-reloading an already-Ready node, keep its pipeline as a fallback
+        // instead of dropping it, so a bad edit doesn't take the node dark.
+        let fallback = match std::mem::replace(&mut self.state, EffectNodeState::Uninitialized) {
+            EffectNodeState::Ready(ready_state) => Some(ready_state),
+            _ => None,
+        };
+        self.state = EffectNodeState::Compiling {shader_compilation_work_handle: Some(shader_compilation_work_handle), fallback};
     }
 
-    // Called when the shader compilation is finished. Sets up the render pipeline that will be used in paint calls, and sets the state to Ready.
-    fn setup_render_pipeline(&mut self, device: &wgpu::Device, frag_binary: &[u8]) {
+    // Called when the shader compilation is finished. Sets up the render pipelines that will be used in paint calls, and sets the state to Ready.
+    fn setup_render_pipeline(&mut self, context: &UpdateContext, device: &wgpu::Device, compiled: &CompiledEffect, reused_uniforms: Option<(wgpu::Buffer, wgpu::BufferAddress, wgpu::BufferAddress)>) {
         let vs_module = device.create_shader_module(wgpu::include_spirv!(concat!(env!("OUT_DIR"), "/effect_vertex.spv")));
-        let fs_module = device.create_shader_module(wgpu::util::make_spirv(frag_binary));
 
-        let n_inputs = 1_u32; // XXX read from file
+        let n_inputs = compiled.input_count;
+        // Floored at 1 even for a single-pass effect, so the paint bind group layout always
+        // has a (possibly unused) `iPassTex[]` slot rather than needing a second layout shape.
+        let max_prev_passes = (compiled.passes.len() as u32).saturating_sub(1).max(1);
+        // Same flooring as `max_prev_passes`, and for the same reason: `#property
+        // channelCount 0` should still leave `iChannelTex[]` a valid (if unused) binding.
+        let max_channels = compiled.channel_count.max(1);
 
         // The effect will have two bind groups, one which will be bound in update() (most uniforms & sampler)
         // and one which will be bound in paint() (a few uniforms & textures)
@@ -138,7 +461,13 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
         // 0: PaintUniforms
         // 1: iInputsTex[]
         // 2: iNoiseTex
-        // 3: iChannelTex[]
+        // 3: iPassTex[]
+        // 4: iChannelTex[]
+        // 5: iOutputTex (compute effects only): the storage texture this pass writes into,
+        //    in place of the render path's implicit color attachment.
+
+        let is_compute = matches!(compiled.kind, EffectKind::Compute { .. });
+        let paint_stage = if is_compute { wgpu::ShaderStage::COMPUTE } else { wgpu::ShaderStage::FRAGMENT };
 
         let update_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             entries: &[
@@ -146,8 +475,11 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
                     binding: 0, // UpdateUniforms
                     visibility: wgpu::ShaderStage::FRAGMENT,
                     ty: wgpu::BindingType::UniformBuffer {
-                        dynamic: false,
-                        min_binding_size: None,
+                        // This node's slot is selected at draw time via a dynamic offset
+                        // into the shared `UniformArena` buffer, rather than baking a
+                        // per-node offset into the bind group itself.
+                        dynamic: true,
+                        min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<UpdateUniforms>() as u64),
                     },
                     count: None,
                 },
@@ -163,118 +495,173 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
             label: Some("update bind group layout"),
         });
 
-        let paint_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0, // PaintUniforms
-                    visibility: wgpu::ShaderStage::FRAGMENT,
-                    ty: wgpu::BindingType::UniformBuffer {
-                        dynamic: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
+        let mut paint_bind_group_layout_entries = vec![
+            wgpu::BindGroupLayoutEntry {
+                binding: 0, // PaintUniforms
+                visibility: paint_stage,
+                ty: wgpu::BindingType::UniformBuffer {
+                    dynamic: true,
+                    min_binding_size: wgpu::BufferSize::new(std::mem::size_of::<PaintUniforms>() as u64),
                 },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1, // iInputsTex
-                    visibility: wgpu::ShaderStage::FRAGMENT,
-                    ty: wgpu::BindingType::SampledTexture {
-                        multisampled: false,
-                        dimension: wgpu::TextureViewDimension::D2,
-                        component_type: wgpu::TextureComponentType::Uint,
-                    },
-                    count: NonZeroU32::new(n_inputs),
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1, // iInputsTex
+                visibility: paint_stage,
+                ty: wgpu::BindingType::SampledTexture {
+                    multisampled: false,
+                    dimension: wgpu::TextureViewDimension::D2,
+                    component_type: wgpu::TextureComponentType::Uint,
                 },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 2, // iNoiseTex
-                    visibility: wgpu::ShaderStage::FRAGMENT,
-                    ty: wgpu::BindingType::SampledTexture {
-                        multisampled: false,
-                        dimension: wgpu::TextureViewDimension::D2,
-                        component_type: wgpu::TextureComponentType::Uint,
-                    },
-                    count: None,
+                count: NonZeroU32::new(n_inputs),
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 2, // iNoiseTex
+                visibility: paint_stage,
+                ty: wgpu::BindingType::SampledTexture {
+                    multisampled: false,
+                    dimension: wgpu::TextureViewDimension::D2,
+                    component_type: wgpu::TextureComponentType::Uint,
                 },
-                //wgpu::BindGroupLayoutEntry {
-                //    binding: 3, // iChannelTex
-                //    visibility: wgpu::ShaderStage::FRAGMENT,
-                //    ty: wgpu::BindingType::SampledTexture {
-                //        multisampled: false,
-                //        dimension: wgpu::TextureViewDimension::D2,
-                //        component_type: wgpu::TextureComponentType::Uint,
-                //    },
-                //    count: NonZeroU32::new(n_inputs),
-                //},
-            ],
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 3, // iPassTex: outputs of earlier passes in this same effect
+                visibility: paint_stage,
+                ty: wgpu::BindingType::SampledTexture {
+                    multisampled: false,
+                    dimension: wgpu::TextureViewDimension::D2,
+                    component_type: wgpu::TextureComponentType::Uint,
+                },
+                count: NonZeroU32::new(max_prev_passes),
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 4, // iChannelTex: this effect's own output from previous frames
+                visibility: paint_stage,
+                ty: wgpu::BindingType::SampledTexture {
+                    multisampled: false,
+                    dimension: wgpu::TextureViewDimension::D2,
+                    component_type: wgpu::TextureComponentType::Uint,
+                },
+                count: NonZeroU32::new(max_channels),
+            },
+        ];
+        if is_compute {
+            paint_bind_group_layout_entries.push(wgpu::BindGroupLayoutEntry {
+                binding: 5, // iOutputTex: this pass's storage-texture output
+                visibility: wgpu::ShaderStage::COMPUTE,
+                ty: wgpu::BindingType::StorageTexture {
+                    dimension: wgpu::TextureViewDimension::D2,
+                    format: compiled.format.storage_format(),
+                    readonly: false,
+                },
+                count: None,
+            });
+        }
+
+        let paint_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &paint_bind_group_layout_entries,
             label: Some("paint bind group layout"),
         });
 
-        let render_pipeline_layout =
+        let pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render Pipeline Layout"),
+                label: Some("Effect Pipeline Layout"),
                 bind_group_layouts: &[&update_bind_group_layout, &paint_bind_group_layout],
                 push_constant_ranges: &[],
             });
 
-        // Create a render pipeline, we will eventually want multiple of these for a multi-pass effect
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex_stage: wgpu::ProgrammableStageDescriptor {
-                module: &vs_module,
-                entry_point: "main",
-            },
-            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
-                module: &fs_module,
-                entry_point: "main",
-            }),
-            rasterization_state: Some(
-                wgpu::RasterizationStateDescriptor {
-                    front_face: wgpu::FrontFace::Cw,
-                    cull_mode: wgpu::CullMode::Back,
-                    depth_bias: 0,
-                    depth_bias_slope_scale: 0.0,
-                    depth_bias_clamp: 0.0,
-                    clamp_depth: false,
-                }
-            ), 
-            color_states: &[
-                wgpu::ColorStateDescriptor {
-                    format: wgpu::TextureFormat::Rgba8UnormSrgb,
-                    color_blend: wgpu::BlendDescriptor::REPLACE,
-                    alpha_blend: wgpu::BlendDescriptor::REPLACE,
-                    write_mask: wgpu::ColorWrite::ALL,
-                },
-            ],
-            primitive_topology: wgpu::PrimitiveTopology::TriangleStrip,
-            depth_stencil_state: None,
-            vertex_state: wgpu::VertexStateDescriptor {
-                index_format: wgpu::IndexFormat::Uint16,
-                vertex_buffers: &[],
-            },
-            sample_count: 1,
-            sample_mask: !0,
-            alpha_to_coverage_enabled: false,
-        });
-
-        // The update uniform buffer for this effect
-        let update_uniform_buffer = device.create_buffer(
-            &wgpu::BufferDescriptor {
-                label: Some("update uniform buffer"),
-                size: std::mem::size_of::<UpdateUniforms>() as u64,
-                usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
-                mapped_at_creation: false,
+        let pipelines = match compiled.kind {
+            EffectKind::Render => {
+                // One render pipeline per `#pass` stage, all sharing the same layout; only the
+                // fragment module (and so the shader source) differs between them.
+                let render_pipelines: Vec<wgpu::RenderPipeline> = compiled
+                    .passes
+                    .iter()
+                    .map(|frag_binary| {
+                        let fs_module = device.create_shader_module(wgpu::util::make_spirv(frag_binary));
+                        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                            label: Some("Render Pipeline"),
+                            layout: Some(&pipeline_layout),
+                            vertex_stage: wgpu::ProgrammableStageDescriptor {
+                                module: &vs_module,
+                                entry_point: "main",
+                            },
+                            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                                module: &fs_module,
+                                entry_point: "main",
+                            }),
+                            rasterization_state: Some(
+                                wgpu::RasterizationStateDescriptor {
+                                    front_face: wgpu::FrontFace::Cw,
+                                    cull_mode: wgpu::CullMode::Back,
+                                    depth_bias: 0,
+                                    depth_bias_slope_scale: 0.0,
+                                    depth_bias_clamp: 0.0,
+                                    clamp_depth: false,
+                                }
+                            ),
+                            color_states: &[
+                                wgpu::ColorStateDescriptor {
+                                    format: compiled.format.render_format(),
+                                    color_blend: wgpu::BlendDescriptor::REPLACE,
+                                    alpha_blend: wgpu::BlendDescriptor::REPLACE,
+                                    write_mask: wgpu::ColorWrite::ALL,
+                                },
+                            ],
+                            primitive_topology: wgpu::PrimitiveTopology::TriangleStrip,
+                            depth_stencil_state: None,
+                            vertex_state: wgpu::VertexStateDescriptor {
+                                index_format: wgpu::IndexFormat::Uint16,
+                                vertex_buffers: &[],
+                            },
+                            sample_count: 1,
+                            sample_mask: !0,
+                            alpha_to_coverage_enabled: false,
+                        })
+                    })
+                    .collect();
+                EffectPipelines::Render(render_pipelines)
             }
-        );
-
-        // The paint uniform buffer for this effect
-        let paint_uniform_buffer = device.create_buffer(
-            &wgpu::BufferDescriptor {
-                label: Some("paint uniform buffer"),
-                size: std::mem::size_of::<PaintUniforms>() as u64,
-                usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
-                mapped_at_creation: false,
+            EffectKind::Compute { workgroup_size } => {
+                // One compute pipeline per `#pass` stage, all sharing the same layout; only
+                // the compute module differs between them, same as the render path above.
+                let compute_pipelines: Vec<wgpu::ComputePipeline> = compiled
+                    .passes
+                    .iter()
+                    .map(|cs_binary| {
+                        let cs_module = device.create_shader_module(wgpu::util::make_spirv(cs_binary));
+                        device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                            label: Some("Compute Pipeline"),
+                            layout: Some(&pipeline_layout),
+                            compute_stage: wgpu::ProgrammableStageDescriptor {
+                                module: &cs_module,
+                                entry_point: "main",
+                            },
+                        })
+                    })
+                    .collect();
+                EffectPipelines::Compute { pipelines: compute_pipelines, workgroup_size }
             }
-        );
+        };
+
+        // This node's permanent slots in the shared uniform arena, rather than a buffer of
+        // its own for each of UpdateUniforms/PaintUniforms. On a hot-reload of an already-Ready
+        // node, `reused_uniforms` carries the slots it already held, so re-compiling in place
+        // doesn't burn two more of the arena's slots every time a shader is saved: the arena
+        // never frees anything (see `UniformArena`'s doc comment), so a live set's worth of
+        // edit/save cycles would otherwise exhaust its fixed capacity.
+        let (uniform_buffer, update_uniform_offset, paint_uniform_offset) = match reused_uniforms {
+            Some((uniform_buffer, update_uniform_offset, paint_uniform_offset)) => {
+                (uniform_buffer, update_uniform_offset, paint_uniform_offset)
+            },
+            None => {
+                let mut arena = context.uniform_arena().borrow_mut();
+                let update_uniform_offset = arena.allocate(std::mem::size_of::<UpdateUniforms>() as wgpu::BufferAddress);
+                let paint_uniform_offset = arena.allocate(std::mem::size_of::<PaintUniforms>() as wgpu::BufferAddress);
+                (arena.buffer().clone(), update_uniform_offset, paint_uniform_offset)
+            },
+        };
 
         // The sampler that will be used for texture access within the shaders
         let sampler = device.create_sampler(
@@ -289,13 +676,16 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
             }
         );
 
-        // The update bind group is actually static, since we will just issue updates the uniform buffer
+        // The update bind group only needs building once: its resource bindings are a
+        // zero-offset, one-slot window into the shared arena buffer plus the sampler, and
+        // this node's actual slot is selected each time it's used via the dynamic offset
+        // passed to `set_bind_group` (see `update_uniform_offset`/`paint_uniform_offset`).
         let update_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
             layout: &update_bind_group_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::Buffer(update_uniform_buffer.slice(..))
+                    resource: wgpu::BindingResource::Buffer(uniform_buffer.slice(0..std::mem::size_of::<UpdateUniforms>() as u64))
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
@@ -306,12 +696,16 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
         });
 
         self.state = EffectNodeState::Ready(ReadyState {
-            render_pipeline,
+            pipelines,
             update_bind_group,
             paint_bind_group_layout,
-            update_uniform_buffer,
-            paint_uniform_buffer,
+            uniform_buffer,
+            update_uniform_offset,
+            paint_uniform_offset,
             n_inputs,
+            max_prev_passes,
+            max_channels,
+            format: compiled.format,
         });
     }
 
@@ -332,30 +726,51 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
         let name_changed = self.name != self.pending.name;
         if name_changed {
             self.name = self.pending.name.clone();
+            self.needs_recompile = false;
             // Always recompile if name changed
             match self.name {
                 Some(_) => {self.start_compiling_shader(context);}
                 None => {self.state = EffectNodeState::Uninitialized;},
             };
-        } else if let EffectNodeState::Compiling {shader_compilation_work_handle: handle_opt} = &mut self.state {
+        } else if self.needs_recompile && matches!(self.state, EffectNodeState::Ready(_) | EffectNodeState::Error(_)) {
+            // A hot-reload: re-trigger compilation without disturbing the pipeline
+            // that's still live underneath EffectNodeState::Compiling (see below).
+            self.needs_recompile = false;
+            self.start_compiling_shader(context);
+        } else if let EffectNodeState::Compiling {shader_compilation_work_handle: handle_opt, ..} = &mut self.state {
             // See if compilation is finished
             let handle_ref = handle_opt.as_ref().unwrap();
-            if !handle_ref.alive() {
+            let finished = !handle_ref.alive();
+            if finished {
                 let handle = handle_opt.take().unwrap();
+                // Pull the fallback out of self.state now, since setup_render_pipeline()
+                // below is about to overwrite it with the newly Ready state anyway.
+                let fallback = match std::mem::replace(&mut self.state, EffectNodeState::Uninitialized) {
+                    EffectNodeState::Compiling {fallback, ..} => fallback,
+                    _ => unreachable!(),
+                };
                 match handle.join() {
-                    WorkResult::Ok(result) => {
-                        match result {
-                            Ok(binary) => {
-                                self.setup_render_pipeline(device, &binary);
-                            },
-                            Err(msg) => {
-                                self.state = EffectNodeState::Error(msg.to_string());
-                                println!("Shader compilation error: {}", msg.to_string());
-                            },
-                        }
+                    WorkResult::Ok(Ok(compiled)) => {
+                        self.last_error = None;
+                        self.last_expanded_source = Some(compiled.expanded_src.clone());
+                        // Reuse the fallback's uniform arena slots, if it had any, instead of
+                        // allocating fresh ones for every successful hot-reload.
+                        let reused_uniforms = fallback.as_ref().map(|ready| {
+                            (ready.uniform_buffer.clone(), ready.update_uniform_offset, ready.paint_uniform_offset)
+                        });
+                        self.setup_render_pipeline(context, device, &compiled, reused_uniforms);
+                    },
+                    WorkResult::Ok(Err(msg)) => {
+                        println!("Shader compilation error: {}", msg);
+                        self.last_error = Some(msg.clone());
+                        // Fall back to the last working pipeline, if there was one,
+                        // rather than going dark on a bad hot-reload edit.
+                        self.state = fallback.map(EffectNodeState::Ready).unwrap_or(EffectNodeState::Error(msg));
                     },
                     WorkResult::Err(_) => {
-                        self.state = EffectNodeState::Error("Shader compilation panicked".to_owned());
+                        let msg = "Shader compilation panicked".to_owned();
+                        self.last_error = Some(msg.clone());
+                        self.state = fallback.map(EffectNodeState::Ready).unwrap_or(EffectNodeState::Error(msg));
                     },
                 }
             }
@@ -363,18 +778,29 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
 
         self.intensity = self.pending.intensity;
 
+        // Difference against the last call's timestamp to get `dt`; on the very first call
+        // there's nothing to difference against, so assume a plausible frame time instead of
+        // a `dt` of 0 (which would leave `iFPS` infinite and the integral stuck).
+        let now = context.time();
+        let dt = match self.last_update_time {
+            Some(last) => (now - last).max(0.),
+            None => 1. / 60.,
+        };
+        self.last_update_time = Some(now);
+        self.last_dt = dt;
+        self.intensity_integral = (self.intensity_integral + self.intensity * dt).rem_euclid(1024.);
+
         if let EffectNodeState::Ready(ready_state) = &mut self.state {
             // Node is ready; we should set the uniforms
-            // TODO set these dynamically, from context()
             let uniforms = UpdateUniforms {
-                iAudio: [0., 0., 0., 0.],
-                iStep: 0., // What's this?
-                iTime: context.time(),
-                iFrequency: 1.,
+                iAudio: context.audio_bands(),
+                iStep: dt,
+                iTime: now,
+                iFrequency: context.beat_phase(),
                 iIntensity: self.intensity,
-                iIntensityIntegral: 0.,
+                iIntensityIntegral: self.intensity_integral,
             };
-            queue.write_buffer(&ready_state.update_uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+            queue.write_buffer(&ready_state.uniform_buffer, ready_state.update_uniform_offset, bytemuck::cast_slice(&[uniforms]));
         }
     }
 
@@ -383,6 +809,24 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
     pub fn new_paint_state<PaintContext: BlankTexture + NoiseTexture + Resolution>(&self, context: &PaintContext, device: &wgpu::Device) -> EffectNodePaintState {
         let (width, height) = context.resolution();
 
+        EffectNodePaintState{
+            input_textures: Vec::new(),
+            pass_textures: Vec::new(),
+            pass_textures_are_storage: false,
+            pass_textures_format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            output_texture: Rc::new(Self::make_intermediate_texture(device, width, height, wgpu::TextureFormat::Rgba8UnormSrgb)),
+            output_is_storage: false,
+            output_format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            channel_textures: Vec::new(),
+        }
+    }
+
+    /// A render-attachment-and-sampleable texture sized to the chain's resolution, used both
+    /// for `EffectNodePaintState::output_texture` and for each non-final pass's intermediate
+    /// texture in `pass_textures` — the two are interchangeable, just written at different
+    /// points in a multi-pass effect's pipeline sequence. `format` is the effect's working
+    /// format (`EffectFormat::render_format()`), `Rgba8UnormSrgb` before a node is Ready.
+    fn make_intermediate_texture(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat) -> Texture {
         let texture_desc = wgpu::TextureDescriptor {
             size: wgpu::Extent3d {
                 width,
@@ -393,7 +837,7 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
             mip_level_count: 1,
             sample_count: 1,
             dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            format,
             usage: wgpu::TextureUsage::COPY_SRC
                 | wgpu::TextureUsage::OUTPUT_ATTACHMENT
                 | wgpu::TextureUsage::SAMPLED
@@ -415,14 +859,93 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
             }
         );
 
-        EffectNodePaintState{
-            input_textures: Vec::new(),
-            output_texture: Rc::new(Texture {
-                texture,
-                view,
-                sampler,
-            }),
+        Texture { texture, view, sampler }
+    }
+
+    /// Like `make_intermediate_texture`, but for a compute effect's output: writable as a
+    /// storage texture from `paint`'s compute pass instead of as a render-pass color
+    /// attachment. `format` is the effect's working format (`EffectFormat::storage_format()`),
+    /// which for `EffectFormat::Srgb8` is `COMPUTE_OUTPUT_FORMAT` rather than the render path's
+    /// `Rgba8UnormSrgb`, since wgpu storage-texture bindings can't use an sRGB format.
+    fn make_storage_texture(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat) -> Texture {
+        let texture_desc = wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsage::COPY_SRC
+                | wgpu::TextureUsage::STORAGE
+                | wgpu::TextureUsage::SAMPLED
+                ,
+            label: None,
+        };
+
+        let texture = device.create_texture(&texture_desc);
+        let view = texture.create_view(&Default::default());
+        let sampler = device.create_sampler(
+            &wgpu::SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Linear,
+                min_filter: wgpu::FilterMode::Linear,
+                mipmap_filter: wgpu::FilterMode::Linear,
+                ..Default::default()
+            }
+        );
+
+        Texture { texture, view, sampler }
+    }
+
+    /// Swaps `paint_state.output_texture` for one with the matching usage/format if the
+    /// effect's kind or working format changed since it was last created — a hot-reload can
+    /// flip `#kind render`/`#kind compute` or `#property format` in place, and the previous
+    /// frame's texture would otherwise be the wrong kind (or format) to bind.
+    fn ensure_output_texture(paint_state: &mut EffectNodePaintState, device: &wgpu::Device, width: u32, height: u32, storage: bool, format: wgpu::TextureFormat) {
+        if paint_state.output_is_storage != storage || paint_state.output_format != format {
+            paint_state.output_texture = Rc::new(if storage {
+                Self::make_storage_texture(device, width, height, format)
+            } else {
+                Self::make_intermediate_texture(device, width, height, format)
+            });
+            paint_state.output_is_storage = storage;
+            paint_state.output_format = format;
+        }
+    }
+
+    /// Grows (or shrinks) `paint_state.pass_textures` to exactly `n_needed` entries, so it
+    /// always matches the current `ReadyState::pipelines.len() - 1` — the effect's pass count
+    /// can change across a hot-reload, and stale extra textures from a previous, longer
+    /// pipeline shouldn't linger. Also rebuilds all of them if `storage` (render vs compute) or
+    /// `format` changed, for the same reason as `ensure_output_texture`.
+    fn ensure_pass_textures(paint_state: &mut EffectNodePaintState, device: &wgpu::Device, width: u32, height: u32, n_needed: usize, storage: bool, format: wgpu::TextureFormat) {
+        if paint_state.pass_textures_are_storage != storage || paint_state.pass_textures_format != format {
+            paint_state.pass_textures.clear();
+            paint_state.pass_textures_are_storage = storage;
+            paint_state.pass_textures_format = format;
+        }
+        while paint_state.pass_textures.len() < n_needed {
+            paint_state.pass_textures.push(Rc::new(if storage {
+                Self::make_storage_texture(device, width, height, format)
+            } else {
+                Self::make_intermediate_texture(device, width, height, format)
+            }));
         }
+        paint_state.pass_textures.truncate(n_needed);
+    }
+
+    /// Shrinks `paint_state.channel_textures` down to `n_needed` entries if the effect's
+    /// channel count dropped on a hot-reload, dropping the oldest (back of the vec) first.
+    /// Unlike `ensure_pass_textures` this never grows eagerly: `channel_textures` only gains
+    /// entries as `paint()` swaps each frame's finished output in, so a freshly (re)compiled
+    /// effect sees blank history until it's actually painted that many frames.
+    fn ensure_channel_textures(paint_state: &mut EffectNodePaintState, n_needed: usize) {
+        paint_state.channel_textures.truncate(n_needed);
     }
 
     /// Updates the given PaintState.
@@ -434,19 +957,20 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
                     label: Some("Render Encoder"),
                 });
 
+                let (width, height) = context.resolution();
                 {
                     // Populate the paint uniforms
-                    let (width, height) = context.resolution();
+                    // Clamped so a single slow/hitched frame (tiny dt) doesn't send effects an
+                    // absurd `iFPS` spike; the first frame's assumed 1/60 dt falls well inside.
+                    let fps = (1. / self.last_dt).clamp(1., 1000.);
                     let uniforms = PaintUniforms {
                         iResolution: [width as f32, height as f32],
-                        iFPS: 60., // TODO set dynamically
+                        iFPS: fps,
                     };
-                    queue.write_buffer(&ready_state.paint_uniform_buffer, 0, bytemuck::cast_slice(&[uniforms]));
+                    queue.write_buffer(&ready_state.uniform_buffer, ready_state.paint_uniform_offset, bytemuck::cast_slice(&[uniforms]));
                 }
 
-                // Populate the paint bind group
-
-                // Make an array of input textures
+                // Make an array of input textures, shared by every pass.
                 // TODO repeatedly creating all these views seems bad,
                 // but TextureViewArray takes in &[TextureView], not &[&TextureView] so it's hard.
                 let input_binding: Vec<wgpu::TextureView> = (0..ready_state.n_inputs).map(|i| {
@@ -459,12 +983,52 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
                     }
                 }).collect();
 
-                let paint_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                    layout: &ready_state.paint_bind_group_layout,
-                    entries: &[
+                let n_passes = ready_state.pipelines.len();
+                // Compute effects write their output (and any intermediate pass) through a
+                // storage-texture binding instead of a render-pass color attachment, so their
+                // textures need a different format/usage than the render path's.
+                let is_compute = matches!(ready_state.pipelines, EffectPipelines::Compute { .. });
+                let texture_format = if is_compute { ready_state.format.storage_format() } else { ready_state.format.render_format() };
+                Self::ensure_output_texture(paint_state, device, width, height, is_compute, texture_format);
+                Self::ensure_pass_textures(paint_state, device, width, height, n_passes.saturating_sub(1), is_compute, texture_format);
+
+                // This effect's own history, shared by every pass (unlike `iPassTex`, which
+                // is per-pass): index 0 is last frame's finished output, index 1 two frames
+                // ago, etc. Slots not yet warmed up (or past `max_channels`) fall back to
+                // blank, same as `input_binding` does for unconnected inputs.
+                Self::ensure_channel_textures(paint_state, ready_state.max_channels as usize);
+                let channel_binding: Vec<wgpu::TextureView> = (0..ready_state.max_channels).map(|i| {
+                    match paint_state.channel_textures.get(i as usize) {
+                        Some(tex) => tex.texture.create_view(&Default::default()),
+                        None => context.blank_texture().texture.create_view(&Default::default()),
+                    }
+                }).collect();
+
+                for pass_index in 0..n_passes {
+                    // Pass N can sample the output of any pass before it; passes at or past
+                    // this one that haven't run yet (or don't exist) fall back to a blank
+                    // texture rather than leaving the binding unfilled.
+                    let prev_pass_binding: Vec<wgpu::TextureView> = (0..ready_state.max_prev_passes).map(|i| {
+                        if (i as usize) < pass_index {
+                            paint_state.pass_textures[i as usize].texture.create_view(&Default::default())
+                        } else {
+                            context.blank_texture().texture.create_view(&Default::default())
+                        }
+                    }).collect();
+
+                    // The last pass writes straight into `output_texture`; every earlier
+                    // pass writes into its own slot in `pass_textures` for later passes to
+                    // sample.
+                    let target_view = if pass_index + 1 == n_passes {
+                        &paint_state.output_texture.view
+                    } else {
+                        &paint_state.pass_textures[pass_index].view
+                    };
+
+                    let mut bind_group_entries = vec![
                         wgpu::BindGroupEntry {
                             binding: 0, // PaintUniforms
-                            resource: wgpu::BindingResource::Buffer(ready_state.paint_uniform_buffer.slice(..))
+                            resource: wgpu::BindingResource::Buffer(ready_state.uniform_buffer.slice(0..std::mem::size_of::<PaintUniforms>() as u64))
                         },
                         wgpu::BindGroupEntry {
                             binding: 1, // iInputsTex
@@ -474,43 +1038,88 @@ impl<UpdateContext: WorkerPool + FetchContent + Timebase> EffectNode<UpdateConte
                             binding: 2, // iNoiseTex
                             resource: wgpu::BindingResource::TextureView(&context.noise_texture().view)
                         },
-                        //wgpu::BindGroupEntry {
-                        //    binding: 3, // iChannelTex
-                        //    resource: wgpu::BindingResource::TextureViewArray()
-                        //},
-                    ],
-                    label: Some("update bind group"),
-                });
+                        wgpu::BindGroupEntry {
+                            binding: 3, // iPassTex
+                            resource: wgpu::BindingResource::TextureViewArray(prev_pass_binding.as_slice())
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 4, // iChannelTex
+                            resource: wgpu::BindingResource::TextureViewArray(channel_binding.as_slice())
+                        },
+                    ];
+                    if is_compute {
+                        bind_group_entries.push(wgpu::BindGroupEntry {
+                            binding: 5, // iOutputTex: this pass's storage-texture output
+                            resource: wgpu::BindingResource::TextureView(target_view),
+                        });
+                    }
 
-                {
-                    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                        color_attachments: &[
-                            wgpu::RenderPassColorAttachmentDescriptor {
-                                attachment: &paint_state.output_texture.view,
-                                resolve_target: None,
-                                ops: wgpu::Operations {
-                                    load: wgpu::LoadOp::Clear(
-                                        wgpu::Color {
-                                            r: 0.1,
-                                            g: 0.2,
-                                            b: 0.3,
-                                            a: 1.0,
-                                        }
-                                    ),
-                                    store: true,
-                                }
-                            }
-                        ],
-                        depth_stencil_attachment: None,
+                    let paint_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                        layout: &ready_state.paint_bind_group_layout,
+                        entries: &bind_group_entries,
+                        label: Some("update bind group"),
                     });
 
-                    render_pass.set_pipeline(&ready_state.render_pipeline);
-                    render_pass.set_bind_group(0, &ready_state.update_bind_group, &[]); 
-                    render_pass.set_bind_group(1, &paint_bind_group, &[]); 
-                    render_pass.draw(0..4, 0..1);
+                    match &ready_state.pipelines {
+                        EffectPipelines::Render(pipelines) => {
+                            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                                color_attachments: &[
+                                    wgpu::RenderPassColorAttachmentDescriptor {
+                                        attachment: target_view,
+                                        resolve_target: None,
+                                        ops: wgpu::Operations {
+                                            load: wgpu::LoadOp::Clear(
+                                                wgpu::Color {
+                                                    r: 0.1,
+                                                    g: 0.2,
+                                                    b: 0.3,
+                                                    a: 1.0,
+                                                }
+                                            ),
+                                            store: true,
+                                        }
+                                    }
+                                ],
+                                depth_stencil_attachment: None,
+                            });
+
+                            render_pass.set_pipeline(&pipelines[pass_index]);
+                            render_pass.set_bind_group(0, &ready_state.update_bind_group, &[ready_state.update_uniform_offset as wgpu::DynamicOffset]);
+                            render_pass.set_bind_group(1, &paint_bind_group, &[ready_state.paint_uniform_offset as wgpu::DynamicOffset]);
+                            render_pass.draw(0..4, 0..1);
+                        }
+                        EffectPipelines::Compute { pipelines, workgroup_size } => {
+                            let mut compute_pass = encoder.begin_compute_pass();
+                            compute_pass.set_pipeline(&pipelines[pass_index]);
+                            compute_pass.set_bind_group(0, &ready_state.update_bind_group, &[ready_state.update_uniform_offset as wgpu::DynamicOffset]);
+                            compute_pass.set_bind_group(1, &paint_bind_group, &[ready_state.paint_uniform_offset as wgpu::DynamicOffset]);
+                            let workgroups_x = (width + workgroup_size.0 - 1) / workgroup_size.0;
+                            let workgroups_y = (height + workgroup_size.1 - 1) / workgroup_size.1;
+                            compute_pass.dispatch(workgroups_x, workgroups_y, 1);
+                        }
+                    }
+                }
+
+                // This frame's result, returned to the caller below. Swap it into
+                // `channel_textures[0]` for next frame's `iChannelTex` and reuse whatever
+                // falls off the back of the ring (if any) as next frame's `output_texture`,
+                // rather than allocating a new one every frame.
+                let finished_output = paint_state.output_texture.clone();
+                if ready_state.max_channels > 0 {
+                    let recycled = if paint_state.channel_textures.len() >= ready_state.max_channels as usize {
+                        paint_state.channel_textures.pop()
+                    } else {
+                        None
+                    };
+                    paint_state.output_texture = recycled.unwrap_or_else(|| Rc::new(if is_compute {
+                        Self::make_storage_texture(device, width, height, texture_format)
+                    } else {
+                        Self::make_intermediate_texture(device, width, height, texture_format)
+                    }));
+                    paint_state.channel_textures.insert(0, finished_output.clone());
                 }
 
-                (vec![encoder.finish()], paint_state.output_texture.clone())
+                (vec![encoder.finish()], finished_output)
             },
             _ => (vec![], context.blank_texture()),
         }
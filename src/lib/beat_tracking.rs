@@ -204,15 +204,14 @@ fn freq2bin(spectrogram_frequencies: SVector<f32, SPECTROGRAM_SIZE>, freq: f32)
     index
 }
 
-// Returns a filter bank according to the given constants
-pub fn gen_filterbank() -> Box<SMatrix<f32, N_FILTERS, SPECTROGRAM_SIZE>> {
+// The per-filter `(note, start, center, stop)` spectrogram-bin layout the triangle filters in
+// `gen_filterbank` are built from, in filter-row order. Shared with `filter_notes` so the
+// duplicate-skipping logic (some adjacent MIDI notes share a bin at this spectrogram's
+// resolution, especially at the low end) lives in exactly one place.
+fn filter_layout() -> Vec<(i32, i32, i32, i32)> {
     let freqs = spectrogram_frequencies();
 
-    let filterbank = [[0_f32; N_FILTERS]; SPECTROGRAM_SIZE];
-    let mut filterbank: Box<SMatrix<f32, N_FILTERS, SPECTROGRAM_SIZE>> = Box::new(SMatrix::from(filterbank));
-
-    // Generate a set of triangle filters
-    let mut filter_index = 0_usize;
+    let mut layout = Vec::with_capacity(N_FILTERS);
     let mut previous_center = -1_i32;
     for note in (FILTER_MIN_NOTE + 1)..=(FILTER_MAX_NOTE - 1) {
         let center = freq2bin(freqs, note2freq(note)) as i32;
@@ -227,17 +226,38 @@ pub fn gen_filterbank() -> Box<SMatrix<f32, N_FILTERS, SPECTROGRAM_SIZE>> {
             start = center - 1;
             stop = center + 1;
         }
-        filterbank.set_row(filter_index, &triangle_filter(start, center, stop).transpose());
-        filter_index += 1;
+        layout.push((note, start, center, stop));
         previous_center = center;
     }
 
     // Check that N_FILTERS constant was set appropriately
-    assert_eq!(filter_index, N_FILTERS);
+    assert_eq!(layout.len(), N_FILTERS);
+
+    layout
+}
+
+// Returns a filter bank according to the given constants
+pub fn gen_filterbank() -> Box<SMatrix<f32, N_FILTERS, SPECTROGRAM_SIZE>> {
+    let filterbank = [[0_f32; N_FILTERS]; SPECTROGRAM_SIZE];
+    let mut filterbank: Box<SMatrix<f32, N_FILTERS, SPECTROGRAM_SIZE>> = Box::new(SMatrix::from(filterbank));
+
+    for (filter_index, &(_, start, center, stop)) in filter_layout().iter().enumerate() {
+        filterbank.set_row(filter_index, &triangle_filter(start, center, stop).transpose());
+    }
 
     filterbank
 }
 
+/// Returns, for each filter row of `gen_filterbank` in order, the MIDI note it's centered on.
+/// Used by `ChromaProcessor` to fold each filter's energy into the pitch class (`note % 12`) it
+/// belongs to.
+fn filter_notes() -> [i32; N_FILTERS] {
+    let mut notes = [0_i32; N_FILTERS];
+    for (i, &(note, ..)) in filter_layout().iter().enumerate() {
+        notes[i] = note;
+    }
+    notes
+}
 
 struct FilteredSpectrogramProcessor {
     filterbank: Box<SMatrix<f32, N_FILTERS, SPECTROGRAM_SIZE>>,
@@ -262,6 +282,117 @@ impl FilteredSpectrogramProcessor {
     }
 }
 
+// Krumhansl-Kessler key profiles: the relative perceived stability of each scale degree in a
+// major/minor key, indexed from the tonic (index 0). Used by `ChromaProcessor::estimate_key` to
+// correlate an observed chroma vector against every rotation of each profile.
+const MAJOR_KEY_PROFILE: [f32; 12] = [6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88];
+const MINOR_KEY_PROFILE: [f32; 12] = [6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17];
+
+/// Rotates a key profile so it's expressed in absolute pitch classes with `tonic` as the root,
+/// i.e. `rotated[pitch_class] = profile[scale degree of pitch_class relative to tonic]`.
+fn rotate_key_profile(profile: &[f32; 12], tonic: usize) -> [f32; 12] {
+    std::array::from_fn(|pitch_class| profile[(pitch_class + 12 - tonic) % 12])
+}
+
+/// The Pearson correlation coefficient between two same-length vectors, used to score how well
+/// an observed chroma vector fits a (rotated) key profile. Returns 0 if either vector has no
+/// variance (e.g. a silent track's all-zero chroma), since correlation is undefined there.
+fn pearson_correlation(a: &[f32; 12], b: &[f32; 12]) -> f32 {
+    let mean_a = a.iter().sum::<f32>() / 12.;
+    let mean_b = b.iter().sum::<f32>() / 12.;
+
+    let mut covariance = 0_f32;
+    let mut variance_a = 0_f32;
+    let mut variance_b = 0_f32;
+    for i in 0..12 {
+        let da = a[i] - mean_a;
+        let db = b[i] - mean_b;
+        covariance += da * db;
+        variance_a += da * da;
+        variance_b += db * db;
+    }
+
+    if variance_a == 0. || variance_b == 0. {
+        0.
+    } else {
+        covariance / (variance_a.sqrt() * variance_b.sqrt())
+    }
+}
+
+/// A track's estimated musical key: `tonic` is a pitch class (0 = C, 1 = C#/Db, ... 11 = B) and
+/// `minor` distinguishes its major/minor mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Key {
+    pub tonic: usize,
+    pub minor: bool,
+}
+
+/// Folds `FilteredSpectrogramProcessor`'s 81 log-frequency filter outputs into a 12-bin
+/// chromagram (one bin per pitch class), summing the filters whose center note falls in the
+/// same pitch class (`note % 12`), since `gen_filterbank`'s filters are already centered on MIDI
+/// notes. Accumulates over an entire track rather than reporting per frame, since key estimation
+/// from chroma is far more stable on the aggregate than on any single frame.
+pub struct ChromaProcessor {
+    notes: [i32; N_FILTERS],
+    accumulated: SVector<f32, 12>,
+    frame_count: usize,
+}
+
+impl ChromaProcessor {
+    pub fn new() -> Self {
+        Self {
+            notes: filter_notes(),
+            accumulated: SVector::from([0_f32; 12]),
+            frame_count: 0,
+        }
+    }
+
+    /// Folds one frame of `FilteredSpectrogramProcessor` output into the running chromagram.
+    pub fn process(&mut self, filtered: &SVector<f32, N_FILTERS>) {
+        for i in 0..N_FILTERS {
+            let pitch_class = self.notes[i].rem_euclid(12) as usize;
+            self.accumulated[pitch_class] += filtered[i];
+        }
+        self.frame_count += 1;
+    }
+
+    /// Returns the mean chroma vector accumulated so far (all-zero before the first `process`).
+    pub fn chroma(&self) -> SVector<f32, 12> {
+        if self.frame_count == 0 {
+            self.accumulated
+        } else {
+            self.accumulated / self.frame_count as f32
+        }
+    }
+
+    /// Estimates the track's key by correlating the mean chroma vector against all 12 rotations
+    /// of both the major and minor Krumhansl profiles, returning the `(tonic, mode)` pair with
+    /// the strongest correlation.
+    pub fn estimate_key(&self) -> Key {
+        let chroma = self.chroma();
+        let chroma: [f32; 12] = std::array::from_fn(|i| chroma[i]);
+
+        let mut best_key = Key { tonic: 0, minor: false };
+        let mut best_correlation = f32::NEG_INFINITY;
+        for tonic in 0..12 {
+            for (profile, minor) in [(&MAJOR_KEY_PROFILE, false), (&MINOR_KEY_PROFILE, true)] {
+                let correlation = pearson_correlation(&chroma, &rotate_key_profile(profile, tonic));
+                if correlation > best_correlation {
+                    best_correlation = correlation;
+                    best_key = Key { tonic, minor };
+                }
+            }
+        }
+        best_key
+    }
+}
+
+impl Default for ChromaProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 struct SpectrogramDifferenceProcessor {
     prev: Option<SVector<f32, N_FILTERS>>,
 }
@@ -316,21 +447,214 @@ impl<const OUTPUT_SIZE: usize, const INPUT_SIZE: usize> FeedForwardLayer<OUTPUT_
     }
 }
 
-struct LSTMLayer {
+// Hidden size of each LSTM in the beat-tracking RNN stack, matching madmom's released
+// `BeatTracker` model (3 bidirectional layers of 25 units each).
+const RNN_HIDDEN_SIZE: usize = 25;
+
+/// A single-direction LSTM recurrence over `INPUT_SIZE`-wide feature frames, producing
+/// `RNN_HIDDEN_SIZE`-wide hidden states. Fixed at the stack's one hidden size (rather than
+/// generic over it too) so the concatenated-width arithmetic `BidirectionalLstm` needs stays
+/// plain `usize` arithmetic over a crate constant instead of a generic const parameter, which
+/// Rust's stable const generics can't do. Holds its own `h`/`c` state between calls to `step`,
+/// so a full sequence is run with `process`, which resets the state first.
+struct LSTMLayer<const INPUT_SIZE: usize> {
+    w_i: Box<SMatrix<f32, RNN_HIDDEN_SIZE, INPUT_SIZE>>,
+    w_f: Box<SMatrix<f32, RNN_HIDDEN_SIZE, INPUT_SIZE>>,
+    w_g: Box<SMatrix<f32, RNN_HIDDEN_SIZE, INPUT_SIZE>>,
+    w_o: Box<SMatrix<f32, RNN_HIDDEN_SIZE, INPUT_SIZE>>,
+    u_i: Box<SMatrix<f32, RNN_HIDDEN_SIZE, RNN_HIDDEN_SIZE>>,
+    u_f: Box<SMatrix<f32, RNN_HIDDEN_SIZE, RNN_HIDDEN_SIZE>>,
+    u_g: Box<SMatrix<f32, RNN_HIDDEN_SIZE, RNN_HIDDEN_SIZE>>,
+    u_o: Box<SMatrix<f32, RNN_HIDDEN_SIZE, RNN_HIDDEN_SIZE>>,
+    b_i: Box<SVector<f32, RNN_HIDDEN_SIZE>>,
+    b_f: Box<SVector<f32, RNN_HIDDEN_SIZE>>,
+    b_g: Box<SVector<f32, RNN_HIDDEN_SIZE>>,
+    b_o: Box<SVector<f32, RNN_HIDDEN_SIZE>>,
+    h: SVector<f32, RNN_HIDDEN_SIZE>,
+    c: SVector<f32, RNN_HIDDEN_SIZE>,
 }
 
-impl LSTMLayer {
-    pub fn new() -> Self {
-        Self {}
+impl<const INPUT_SIZE: usize> LSTMLayer<INPUT_SIZE> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        w_i: Box<SMatrix<f32, RNN_HIDDEN_SIZE, INPUT_SIZE>>,
+        w_f: Box<SMatrix<f32, RNN_HIDDEN_SIZE, INPUT_SIZE>>,
+        w_g: Box<SMatrix<f32, RNN_HIDDEN_SIZE, INPUT_SIZE>>,
+        w_o: Box<SMatrix<f32, RNN_HIDDEN_SIZE, INPUT_SIZE>>,
+        u_i: Box<SMatrix<f32, RNN_HIDDEN_SIZE, RNN_HIDDEN_SIZE>>,
+        u_f: Box<SMatrix<f32, RNN_HIDDEN_SIZE, RNN_HIDDEN_SIZE>>,
+        u_g: Box<SMatrix<f32, RNN_HIDDEN_SIZE, RNN_HIDDEN_SIZE>>,
+        u_o: Box<SMatrix<f32, RNN_HIDDEN_SIZE, RNN_HIDDEN_SIZE>>,
+        b_i: Box<SVector<f32, RNN_HIDDEN_SIZE>>,
+        b_f: Box<SVector<f32, RNN_HIDDEN_SIZE>>,
+        b_g: Box<SVector<f32, RNN_HIDDEN_SIZE>>,
+        b_o: Box<SVector<f32, RNN_HIDDEN_SIZE>>,
+    ) -> Self {
+        Self {
+            w_i, w_f, w_g, w_o,
+            u_i, u_f, u_g, u_o,
+            b_i, b_f, b_g, b_o,
+            h: SVector::from([0_f32; RNN_HIDDEN_SIZE]),
+            c: SVector::from([0_f32; RNN_HIDDEN_SIZE]),
+        }
+    }
+
+    /// Zero-initialized weights, so a `NeuralNetwork` can exist (and produce a deterministic,
+    /// if useless, activation) before real madmom parameters are loaded via `load`.
+    pub fn zeroed() -> Self {
+        Self::new(
+            Box::new(SMatrix::from([[0_f32; RNN_HIDDEN_SIZE]; INPUT_SIZE])),
+            Box::new(SMatrix::from([[0_f32; RNN_HIDDEN_SIZE]; INPUT_SIZE])),
+            Box::new(SMatrix::from([[0_f32; RNN_HIDDEN_SIZE]; INPUT_SIZE])),
+            Box::new(SMatrix::from([[0_f32; RNN_HIDDEN_SIZE]; INPUT_SIZE])),
+            Box::new(SMatrix::from([[0_f32; RNN_HIDDEN_SIZE]; RNN_HIDDEN_SIZE])),
+            Box::new(SMatrix::from([[0_f32; RNN_HIDDEN_SIZE]; RNN_HIDDEN_SIZE])),
+            Box::new(SMatrix::from([[0_f32; RNN_HIDDEN_SIZE]; RNN_HIDDEN_SIZE])),
+            Box::new(SMatrix::from([[0_f32; RNN_HIDDEN_SIZE]; RNN_HIDDEN_SIZE])),
+            Box::new(SVector::from([0_f32; RNN_HIDDEN_SIZE])),
+            Box::new(SVector::from([0_f32; RNN_HIDDEN_SIZE])),
+            Box::new(SVector::from([0_f32; RNN_HIDDEN_SIZE])),
+            Box::new(SVector::from([0_f32; RNN_HIDDEN_SIZE])),
+        )
+    }
+
+    /// Reads this layer's twelve weight/bias arrays off the front of `data`, in the order
+    /// madmom exports an LSTM's parameters: `w_i, w_f, w_g, w_o, u_i, u_f, u_g, u_o, b_i, b_f,
+    /// b_g, b_o`, each in row-major order.
+    fn load(data: &mut impl Iterator<Item = f32>) -> Self {
+        let matrix = |data: &mut dyn Iterator<Item = f32>, rows: usize, cols: usize| {
+            data.take(rows * cols).collect::<Vec<_>>()
+        };
+        Self::new(
+            Box::new(SMatrix::from_row_iterator(matrix(data, RNN_HIDDEN_SIZE, INPUT_SIZE))),
+            Box::new(SMatrix::from_row_iterator(matrix(data, RNN_HIDDEN_SIZE, INPUT_SIZE))),
+            Box::new(SMatrix::from_row_iterator(matrix(data, RNN_HIDDEN_SIZE, INPUT_SIZE))),
+            Box::new(SMatrix::from_row_iterator(matrix(data, RNN_HIDDEN_SIZE, INPUT_SIZE))),
+            Box::new(SMatrix::from_row_iterator(matrix(data, RNN_HIDDEN_SIZE, RNN_HIDDEN_SIZE))),
+            Box::new(SMatrix::from_row_iterator(matrix(data, RNN_HIDDEN_SIZE, RNN_HIDDEN_SIZE))),
+            Box::new(SMatrix::from_row_iterator(matrix(data, RNN_HIDDEN_SIZE, RNN_HIDDEN_SIZE))),
+            Box::new(SMatrix::from_row_iterator(matrix(data, RNN_HIDDEN_SIZE, RNN_HIDDEN_SIZE))),
+            Box::new(SVector::from_iterator(matrix(data, RNN_HIDDEN_SIZE, 1))),
+            Box::new(SVector::from_iterator(matrix(data, RNN_HIDDEN_SIZE, 1))),
+            Box::new(SVector::from_iterator(matrix(data, RNN_HIDDEN_SIZE, 1))),
+            Box::new(SVector::from_iterator(matrix(data, RNN_HIDDEN_SIZE, 1))),
+        )
+    }
+
+    fn reset(&mut self) {
+        self.h = SVector::from([0_f32; RNN_HIDDEN_SIZE]);
+        self.c = SVector::from([0_f32; RNN_HIDDEN_SIZE]);
+    }
+
+    /// Advances the recurrence by one timestep and returns the new hidden state.
+    fn step(&mut self, x: &SVector<f32, INPUT_SIZE>) -> SVector<f32, RNN_HIDDEN_SIZE> {
+        let i = (*self.w_i * x + *self.u_i * self.h + *self.b_i).map(sigmoid);
+        let f = (*self.w_f * x + *self.u_f * self.h + *self.b_f).map(sigmoid);
+        let g = (*self.w_g * x + *self.u_g * self.h + *self.b_g).map(|v: f32| v.tanh());
+        let o = (*self.w_o * x + *self.u_o * self.h + *self.b_o).map(sigmoid);
+
+        self.c = f.component_mul(&self.c) + i.component_mul(&g);
+        self.h = o.component_mul(&self.c.map(|v: f32| v.tanh()));
+        self.h
+    }
+
+    /// Runs the full sequence `frames` through the recurrence from a freshly reset state,
+    /// returning one hidden state per frame.
+    fn process(&mut self, frames: &[SVector<f32, INPUT_SIZE>]) -> Vec<SVector<f32, RNN_HIDDEN_SIZE>> {
+        self.reset();
+        frames.iter().map(|frame| self.step(frame)).collect()
+    }
+}
+
+/// Runs one `LSTMLayer` forward over a frame sequence and another backward, concatenating the
+/// two hidden states at each timestep. This is the "bidirectional" half of madmom's RNN beat
+/// tracker: the backward pass lets an activation at time `t` take a little bit of future
+/// context into account, not just the past.
+struct BidirectionalLstm<const INPUT_SIZE: usize> {
+    forward: LSTMLayer<INPUT_SIZE>,
+    backward: LSTMLayer<INPUT_SIZE>,
+}
+
+impl<const INPUT_SIZE: usize> BidirectionalLstm<INPUT_SIZE> {
+    pub fn zeroed() -> Self {
+        Self {
+            forward: LSTMLayer::zeroed(),
+            backward: LSTMLayer::zeroed(),
+        }
+    }
+
+    fn load(data: &mut impl Iterator<Item = f32>) -> Self {
+        Self {
+            forward: LSTMLayer::load(data),
+            backward: LSTMLayer::load(data),
+        }
+    }
+
+    fn process(&mut self, frames: &[SVector<f32, INPUT_SIZE>]) -> Vec<SVector<f32, {RNN_HIDDEN_SIZE * 2}>> {
+        let forward_states = self.forward.process(frames);
+
+        let reversed: Vec<_> = frames.iter().rev().cloned().collect();
+        let mut backward_states = self.backward.process(&reversed);
+        backward_states.reverse();
+
+        forward_states.iter().zip(backward_states.iter()).map(|(fwd, bwd)| {
+            let mut concatenated = [0_f32; RNN_HIDDEN_SIZE * 2];
+            concatenated[0..RNN_HIDDEN_SIZE].copy_from_slice(fwd.as_slice());
+            concatenated[RNN_HIDDEN_SIZE..RNN_HIDDEN_SIZE * 2].copy_from_slice(bwd.as_slice());
+            SVector::from(concatenated)
+        }).collect()
     }
 }
 
+/// The madmom-style RNN beat-activation network: three stacked bidirectional LSTM layers
+/// followed by a single sigmoid output unit, turning each `SVector<f32, {N_FILTERS * 2}>`
+/// feature frame (a `FilteredSpectrogramProcessor` frame concatenated with its clamped
+/// difference from the previous frame) into a scalar beat-activation in `[0, 1]`.
 struct NeuralNetwork {
+    layer1: BidirectionalLstm<{N_FILTERS * 2}>,
+    layer2: BidirectionalLstm<{RNN_HIDDEN_SIZE * 2}>,
+    layer3: BidirectionalLstm<{RNN_HIDDEN_SIZE * 2}>,
+    output: FeedForwardLayer<1, {RNN_HIDDEN_SIZE * 2}>,
 }
 
 impl NeuralNetwork {
+    /// An untrained network (all weights zero), so a `BeatTracker` can exist before real
+    /// parameters are loaded via `load`.
     pub fn new() -> Self {
-        Self {}
+        Self {
+            layer1: BidirectionalLstm::zeroed(),
+            layer2: BidirectionalLstm::zeroed(),
+            layer3: BidirectionalLstm::zeroed(),
+            output: FeedForwardLayer::new(
+                Box::new(SMatrix::from([[0_f32]; RNN_HIDDEN_SIZE * 2])),
+                Box::new(SVector::from([0_f32])),
+            ),
+        }
+    }
+
+    /// Populates every layer's weights from `data`, a flat sequence of `f32`s in the order
+    /// madmom's exported RNN parameters are laid out: `layer1`, `layer2`, `layer3` (each as
+    /// forward-then-backward `LSTMLayer` parameters), followed by the output layer's weight
+    /// matrix then its bias.
+    pub fn load(data: &[f32]) -> Self {
+        let mut data = data.iter().copied();
+        Self {
+            layer1: BidirectionalLstm::load(&mut data),
+            layer2: BidirectionalLstm::load(&mut data),
+            layer3: BidirectionalLstm::load(&mut data),
+            output: FeedForwardLayer::new(
+                Box::new(SMatrix::from_row_iterator(data.by_ref().take(RNN_HIDDEN_SIZE * 2))),
+                Box::new(SVector::from_iterator(data.by_ref().take(1))),
+            ),
+        }
+    }
+
+    /// Runs the full bidirectional stack over `frames` and returns one activation per frame.
+    pub fn process(&mut self, frames: &[SVector<f32, {N_FILTERS * 2}>]) -> Vec<f32> {
+        let h1 = self.layer1.process(frames);
+        let h2 = self.layer2.process(&h1);
+        let h3 = self.layer3.process(&h2);
+        h3.iter().map(|h| self.output.process(*h)[0]).collect()
     }
 }
 
@@ -340,6 +664,7 @@ struct BeatTracker {
     stft_processor: ShortTimeFourierTransformProcessor,
     filter_processor: FilteredSpectrogramProcessor,
     difference_processor: SpectrogramDifferenceProcessor,
+    nn: NeuralNetwork,
 }
 
 impl BeatTracker {
@@ -349,20 +674,174 @@ impl BeatTracker {
             stft_processor: ShortTimeFourierTransformProcessor::new(),
             filter_processor: FilteredSpectrogramProcessor::new(),
             difference_processor: SpectrogramDifferenceProcessor::new(),
+            nn: NeuralNetwork::new(),
         }
     }
 
-    pub fn process(&mut self, samples: &[i16]) -> Vec<SVector<f32, {N_FILTERS * 2}>> {
+    /// Replaces the RNN's zero-initialized weights with real madmom-exported parameters.
+    /// See `NeuralNetwork::load` for the expected layout of `data`.
+    pub fn load_weights(&mut self, data: &[f32]) {
+        self.nn = NeuralNetwork::load(data);
+    }
+
+    pub fn process(&mut self, samples: &[i16]) -> Vec<f32> {
         println!("Processing {:?} samples", samples.len());
         let frames = self.framed_processor.process(samples);
         println!("Yielded {:?} frames", frames.len());
-        frames.iter().map(|frame| {
+        let diffs: Vec<_> = frames.iter().map(|frame| {
             let spectrogram = self.stft_processor.process(frame);
             let filtered = self.filter_processor.process(&spectrogram);
-            let diff = self.difference_processor.process(&filtered);
-            // TODO: NN
-            diff
-        }).collect()
+            self.difference_processor.process(&filtered)
+        }).collect();
+        self.nn.process(&diffs)
+    }
+}
+
+// Frame rate of the activation curve produced by `BeatTracker::process`, i.e. `SAMPLE_RATE /
+// HOP_SIZE`.
+const FRAME_RATE: f32 = (SAMPLE_RATE / HOP_SIZE) as f32;
+
+// Tempo search range for `estimate_period`, spanning the tempi a beat-synced visualizer is
+// likely to encounter.
+const MIN_BPM: f32 = 40.;
+const MAX_BPM: f32 = 250.;
+
+/// Returns the lag range, in frames at `FRAME_RATE`, corresponding to `MIN_BPM..=MAX_BPM`.
+/// Faster tempos have shorter beat periods, so `MAX_BPM` gives the lower bound.
+fn tempo_lag_range() -> (usize, usize) {
+    let min_lag = ((FRAME_RATE * 60. / MAX_BPM).round() as usize).max(1);
+    let max_lag = (FRAME_RATE * 60. / MIN_BPM).round() as usize;
+    (min_lag, max_lag)
+}
+
+/// Autocorrelates `activation` at every lag `tau` in `min_lag..=max_lag`, i.e. `r[tau] =
+/// sum_t activation[t] * activation[t + tau]`. The result is indexed from 0, corresponding to
+/// `min_lag`.
+fn autocorrelate(activation: &[f32], min_lag: usize, max_lag: usize) -> Vec<f32> {
+    (min_lag..=max_lag).map(|tau| {
+        if tau >= activation.len() {
+            0.
+        } else {
+            (0..activation.len() - tau).map(|t| activation[t] * activation[t + tau]).sum()
+        }
+    }).collect()
+}
+
+/// Sharpens the raw autocorrelation `r` (indexed from `min_lag`, as returned by `autocorrelate`)
+/// into a comb-filter response, summing each lag's contribution at its harmonics (`r[tau] +
+/// r[2*tau] + r[3*tau] + ...`). This collapses a tempo's octave ambiguity (the autocorrelation
+/// peaks just as strongly at a half or third of the true beat period) onto the fundamental.
+fn comb_filter(r: &[f32], min_lag: usize, max_lag: usize) -> Vec<f32> {
+    (min_lag..=max_lag).map(|tau| {
+        let mut sum = 0.;
+        let mut harmonic = tau;
+        while harmonic <= max_lag {
+            sum += r[harmonic - min_lag];
+            harmonic += tau;
+        }
+        sum
+    }).collect()
+}
+
+/// Estimates the dominant beat period, in frames at `FRAME_RATE`, by autocorrelating
+/// `activation` over the `MIN_BPM..=MAX_BPM` lag range, comb-filtering to sharpen the result,
+/// and returning the strongest lag.
+fn estimate_period(activation: &[f32]) -> f32 {
+    let (min_lag, max_lag) = tempo_lag_range();
+    let r = autocorrelate(activation, min_lag, max_lag);
+    let combed = comb_filter(&r, min_lag, max_lag);
+    let best_index = combed.iter().enumerate()
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(index, _)| index)
+        .unwrap_or(0);
+    (min_lag + best_index) as f32
+}
+
+// Weight of the tempo-deviation term in `dp_beat_track`'s transition cost, trading off how
+// strongly beat intervals are pulled toward `period` against how strongly beats are pulled
+// toward high-activation frames.
+const TEMPO_DEVIATION_PENALTY: f32 = 100.;
+
+/// Finds the highest-scoring beat sequence through `activation` by dynamic programming over a
+/// state space of (frame, is a beat placed here). The best cumulative score reaching frame `t`
+/// either places the first beat there, or extends from an earlier beat in the
+/// `[-2*period, -0.5*period]` window before it, charged a cost proportional to the squared log
+/// ratio of that interval to `period` (so playing too fast and too slow by the same factor cost
+/// the same). Backtraces from the frame with the best overall score to recover the sequence, in
+/// frames.
+fn dp_beat_track(activation: &[f32], period: f32) -> Vec<usize> {
+    let n = activation.len();
+    let mut cumulative_score = vec![0_f32; n];
+    let mut backlink: Vec<Option<usize>> = vec![None; n];
+
+    let min_dt = (-2. * period).round() as i32;
+    let max_dt = (-0.5 * period).round() as i32;
+
+    for t in 0..n {
+        // Placing the first beat at `t` scores 0, so an earlier beat is only linked when
+        // extending from it scores higher than that.
+        let mut best_transition = 0_f32;
+        let mut best_prev = None;
+        for dt in min_dt..=max_dt {
+            let prev = t as i32 + dt;
+            if prev < 0 {
+                continue;
+            }
+            let interval = -dt as f32;
+            let cost = TEMPO_DEVIATION_PENALTY * (interval / period).ln().powi(2);
+            let score = cumulative_score[prev as usize] - cost;
+            if score > best_transition {
+                best_transition = score;
+                best_prev = Some(prev as usize);
+            }
+        }
+        cumulative_score[t] = activation[t] + best_transition;
+        backlink[t] = best_prev;
+    }
+
+    let mut current = cumulative_score.iter().enumerate()
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(index, _)| index);
+
+    let mut beats = Vec::new();
+    while let Some(i) = current {
+        beats.push(i);
+        current = backlink[i];
+    }
+    beats.reverse();
+    beats
+}
+
+/// Turns a `BeatTracker` activation curve into actual beat timestamps. A post-processing stage
+/// over the RNN's per-frame activation: estimates the dominant tempo by autocorrelating (and
+/// comb-filter-sharpening) the activation curve, then runs a dynamic-programming pass that
+/// rewards high-activation frames while penalizing beat intervals that stray from the estimated
+/// period, backtracing the highest-scoring path to recover the beat sequence.
+pub struct BeatTimeTracker;
+
+impl BeatTimeTracker {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Estimates the dominant tempo of `activation`, in beats per minute.
+    pub fn tempo_bpm(&self, activation: &[f32]) -> f32 {
+        FRAME_RATE * 60. / estimate_period(activation)
+    }
+
+    /// Returns beat positions, in seconds from the start of `activation`.
+    pub fn track(&self, activation: &[f32]) -> Vec<f32> {
+        if activation.is_empty() {
+            return Vec::new();
+        }
+        let period = estimate_period(activation);
+        dp_beat_track(activation, period).into_iter().map(|frame| frame as f32 / FRAME_RATE).collect()
+    }
+}
+
+impl Default for BeatTimeTracker {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -471,6 +950,83 @@ mod tests {
         assert_eq!(filterbank[(80,734)], 0.023529412);
     }
 
+    #[test]
+    fn test_filter_notes() {
+        let notes = filter_notes();
+        assert_eq!(notes.len(), N_FILTERS);
+        // First few filters, and the lowest couple of duplicate-skipped notes
+        assert_eq!(notes[0], 24);
+        assert_eq!(notes[1], 33);
+        assert_eq!(notes[2], 39);
+        // Highest notes are dense (one filter per MIDI note, no skipping)
+        assert_eq!(notes[N_FILTERS - 1], 131);
+    }
+
+    #[test]
+    fn test_rotate_key_profile() {
+        let rotated = rotate_key_profile(&MAJOR_KEY_PROFILE, 0);
+        assert_eq!(rotated, MAJOR_KEY_PROFILE);
+
+        let rotated = rotate_key_profile(&MAJOR_KEY_PROFILE, 1);
+        // Rotating by 1 semitone moves the tonic's peak from pitch class 0 to pitch class 1
+        assert_eq!(rotated[1], MAJOR_KEY_PROFILE[0]);
+        assert_eq!(rotated[0], MAJOR_KEY_PROFILE[11]);
+    }
+
+    #[test]
+    fn test_pearson_correlation() {
+        let a = [1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12.];
+        assert_eq!(pearson_correlation(&a, &a), 1.);
+
+        let flat = [5.; 12];
+        assert_eq!(pearson_correlation(&a, &flat), 0.);
+    }
+
+    #[test]
+    fn test_chroma_processor_folds_filters_into_pitch_classes() {
+        let mut chroma = ChromaProcessor::new();
+        let mut filtered = [0_f32; N_FILTERS];
+        filtered[0] = 1.; // note 24, pitch class 0
+        filtered[4] = 1.; // note 47, pitch class 11
+        chroma.process(&SVector::from(filtered));
+
+        let result = chroma.chroma();
+        assert_eq!(result[0], 1.);
+        assert_eq!(result[11], 1.);
+        assert_eq!(result.iter().sum::<f32>(), 2.);
+    }
+
+    #[test]
+    fn test_chroma_processor_averages_over_frames() {
+        let mut chroma = ChromaProcessor::new();
+        let mut frame = [0_f32; N_FILTERS];
+        frame[0] = 1.;
+        chroma.process(&SVector::from(frame));
+        frame[0] = 3.;
+        chroma.process(&SVector::from(frame));
+
+        assert_eq!(chroma.chroma()[0], 2.);
+    }
+
+    #[test]
+    fn test_chroma_processor_estimate_key_matches_pure_major_profile() {
+        let mut chroma = ChromaProcessor::new();
+        // Feed a chroma vector that's an exact C-major profile; C major should win outright.
+        for pitch_class in 0..12 {
+            let mut frame = [0_f32; N_FILTERS];
+            for (i, &note) in filter_notes().iter().enumerate() {
+                if (note as usize) % 12 == pitch_class {
+                    frame[i] = MAJOR_KEY_PROFILE[pitch_class];
+                    break;
+                }
+            }
+            chroma.process(&SVector::from(frame));
+        }
+
+        let key = chroma.estimate_key();
+        assert_eq!(key, Key { tonic: 0, minor: false });
+    }
+
     #[test]
     fn test_spectrogram_difference_processor() {
         let mut data = SVector::from([0_f32; N_FILTERS]);
@@ -512,20 +1068,112 @@ mod tests {
         assert_eq!(out[1], sigmoid(5.6));
     }
 
+    #[test]
+    fn test_lstm_layer_zeroed() {
+        let mut layer = LSTMLayer::<4>::zeroed();
+        let x = SVector::from([1_f32, 2., 3., 4.]);
+        let h = layer.step(&x);
+        // Every weight/bias is zero, so every gate's pre-activation is zero: i = f = o =
+        // sigmoid(0) = 0.5, g = tanh(0) = 0, giving c_t = f*0 + i*0 = 0 and h_t = o*tanh(0) = 0.
+        assert_eq!(h, SVector::from([0_f32; RNN_HIDDEN_SIZE]));
+    }
+
+    #[test]
+    fn test_neural_network_zeroed_gives_constant_activation() {
+        let mut nn = NeuralNetwork::new();
+        let frames = vec![SVector::from([0_f32; N_FILTERS * 2]); 3];
+        let activation = nn.process(&frames);
+        // With every weight zero, the whole bidirectional stack collapses to the output layer's
+        // bias-only sigmoid, i.e. sigmoid(0) = 0.5, for every frame.
+        assert_eq!(activation, vec![0.5, 0.5, 0.5]);
+    }
+
+    // Builds a synthetic activation curve with impulses spaced `period` frames apart, starting
+    // at frame `period`, for `n_beats` beats, padded to `total_frames`.
+    fn synthetic_activation(period: usize, n_beats: usize, total_frames: usize) -> Vec<f32> {
+        let mut activation = vec![0_f32; total_frames];
+        for beat in 1..=n_beats {
+            let frame = beat * period;
+            if frame < total_frames {
+                activation[frame] = 1.;
+            }
+        }
+        activation
+    }
+
+    #[test]
+    fn test_autocorrelate() {
+        let activation = synthetic_activation(10, 20, 220);
+        let r = autocorrelate(&activation, 5, 15);
+        // The autocorrelation should peak at the true period (lag 10), offset by -5 into `r`.
+        let (best_index, _) = r.iter().enumerate().max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap()).unwrap();
+        assert_eq!(best_index + 5, 10);
+    }
+
+    #[test]
+    fn test_comb_filter() {
+        // r indexed from min_lag = 2: lags 2, 3, 4, 5, 6
+        let r = vec![1., 5., 1., 1., 1.];
+        let combed = comb_filter(&r, 2, 6);
+        // lag 2's harmonics are 2, 4, 6 -> r[2] + r[4] + r[6] = 1 + 1 + 1 = 3
+        assert_eq!(combed[0], 3.);
+        // lag 3's harmonics are 3, 6 -> r[3] + r[6] = 5 + 1 = 6
+        assert_eq!(combed[1], 6.);
+    }
+
+    #[test]
+    fn test_estimate_period() {
+        // 150 BPM at the 100 Hz frame rate has a period of 6000/150 = 40 frames.
+        let activation = synthetic_activation(40, 20, 900);
+        assert_eq!(estimate_period(&activation), 40.);
+    }
+
+    #[test]
+    fn test_dp_beat_track_recovers_synthetic_beats() {
+        let activation = synthetic_activation(25, 10, 260);
+        let beats = dp_beat_track(&activation, 25.);
+        let expected: Vec<usize> = (1..=10).map(|beat| beat * 25).collect();
+        assert_eq!(beats, expected);
+    }
+
+    #[test]
+    fn test_beat_time_tracker_track() {
+        let activation = synthetic_activation(30, 15, 480);
+        let tracker = BeatTimeTracker::new();
+        let beats = tracker.track(&activation);
+        assert_eq!(beats.len(), 15);
+        // Beat period of 30 frames at 100 Hz is 0.3 seconds apart.
+        assert_eq!(beats[1] - beats[0], 0.3);
+    }
+
+    #[test]
+    fn test_beat_time_tracker_tempo_bpm() {
+        // A period of 25 frames at 100 Hz is 240 BPM.
+        let activation = synthetic_activation(25, 20, 550);
+        let tracker = BeatTimeTracker::new();
+        assert_eq!(tracker.tempo_bpm(&activation), 240.);
+    }
+
+    #[test]
+    fn test_beat_time_tracker_empty() {
+        let tracker = BeatTimeTracker::new();
+        assert_eq!(tracker.track(&[]), Vec::<f32>::new());
+    }
+
     #[ignore]
     #[test]
     fn test_music() {
         use std::fs::File;
         use std::path::Path;
+        use crate::resample::AudioFrontend;
 
-        // Read music from audio file
+        // Read music from audio file. Regardless of the file's actual channel count, sample
+        // rate, or bit depth, AudioFrontend adapts it to the mono 44100 Hz i16 PCM BeatTracker
+        // requires.
         let mut inp_file = File::open(Path::new("src/lib/test/frontier.wav")).unwrap();
         let (header, data) = wav::read(&mut inp_file).unwrap();
         assert_eq!(header.audio_format, wav::WAV_FORMAT_PCM);
-        assert_eq!(header.channel_count, 1);
-        assert_eq!(header.sampling_rate, 44100);
-        assert_eq!(header.bits_per_sample, 16);
-        let data = data.try_into_sixteen().unwrap();
+        let data = AudioFrontend::from_wav(&header, &data);
 
         println!("WAV file has {:?} samples", data.len());
 
@@ -0,0 +1,266 @@
+// A Shazam-style constellation fingerprinting subsystem, built on the magnitude spectrogram
+// frames `ShortTimeFourierTransformProcessor` already produces: pick dominant time-frequency
+// peaks, pair each with a handful of peaks just ahead of it in time, and hash each pair into a
+// compact key. Matching a query's hashes against a `FingerprintIndex` of known tracks recognizes
+// which track is playing, even under noise that would defeat a plain spectrogram comparison.
+
+use std::collections::HashMap;
+use nalgebra::SVector;
+use crate::beat_tracking::SPECTROGRAM_SIZE;
+
+// How far (in frames/bins) a candidate peak's neighborhood extends when checking whether it
+// dominates its surroundings.
+const PEAK_TIME_RADIUS: usize = 5;
+const PEAK_FREQ_RADIUS: usize = 10;
+
+// How many of the strongest peaks survive pruning, per time slice. Caps the constellation map's
+// density so noise-induced local maxima don't drown out the landmarks that are stable across
+// playback conditions.
+const PEAKS_PER_FRAME: usize = 5;
+
+// The forward target zone an anchor peak is paired against: target peaks must be strictly ahead
+// of the anchor in time, within this frame-offset range, and capped to `FAN_OUT` pairs so the
+// fingerprint count grows linearly (not quadratically) with the constellation's size.
+const TARGET_ZONE_MIN_DT: usize = 1;
+const TARGET_ZONE_MAX_DT: usize = 100;
+const FAN_OUT: usize = 5;
+
+// Bit widths used to pack `(freq_anchor, freq_target, dt)` into a single hash. 10 bits covers
+// every bin of `SPECTROGRAM_SIZE` (1024); 12 bits covers every `dt` up to `TARGET_ZONE_MAX_DT`.
+const FREQ_BITS: u32 = 10;
+const DT_BITS: u32 = 12;
+
+/// A single time-frequency landmark: spectrogram bin `freq` dominates its neighborhood at frame
+/// `time`, with magnitude `magnitude`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Peak {
+    pub time: usize,
+    pub freq: usize,
+    pub magnitude: f32,
+}
+
+/// Finds every local magnitude maximum across `frames` (a bin whose magnitude is at least as
+/// large as every other bin within `PEAK_TIME_RADIUS` frames and `PEAK_FREQ_RADIUS` bins of it),
+/// then prunes each time slice down to its `PEAKS_PER_FRAME` strongest peaks to form a
+/// noise-robust constellation map.
+pub fn find_peaks(frames: &[SVector<f32, SPECTROGRAM_SIZE>]) -> Vec<Peak> {
+    let mut by_time: HashMap<usize, Vec<Peak>> = HashMap::new();
+
+    for t in 0..frames.len() {
+        for f in 0..SPECTROGRAM_SIZE {
+            let magnitude = frames[t][f];
+
+            let t_lo = t.saturating_sub(PEAK_TIME_RADIUS);
+            let t_hi = (t + PEAK_TIME_RADIUS).min(frames.len() - 1);
+            let f_lo = f.saturating_sub(PEAK_FREQ_RADIUS);
+            let f_hi = (f + PEAK_FREQ_RADIUS).min(SPECTROGRAM_SIZE - 1);
+
+            let is_peak = (t_lo..=t_hi).all(|nt| {
+                (f_lo..=f_hi).all(|nf| nt == t && nf == f || frames[nt][nf] <= magnitude)
+            });
+
+            if is_peak && magnitude > 0. {
+                by_time.entry(t).or_default().push(Peak { time: t, freq: f, magnitude });
+            }
+        }
+    }
+
+    let mut peaks = Vec::new();
+    for slice in by_time.values_mut() {
+        slice.sort_by(|a, b| b.magnitude.partial_cmp(&a.magnitude).unwrap());
+        slice.truncate(PEAKS_PER_FRAME);
+        peaks.extend(slice.iter().copied());
+    }
+    peaks.sort_by_key(|peak| peak.time);
+    peaks
+}
+
+/// Packs an anchor/target peak pair into a compact hash: `freq_anchor` and `freq_target` each
+/// get `FREQ_BITS`, and `dt` (the time gap between them) gets `DT_BITS`, laid out from the most
+/// to least significant bits in that order.
+pub fn hash_pair(anchor: &Peak, target: &Peak) -> u64 {
+    let dt = (target.time - anchor.time) as u64;
+    ((anchor.freq as u64) << (FREQ_BITS + DT_BITS))
+        | ((target.freq as u64) << DT_BITS)
+        | (dt & ((1 << DT_BITS) - 1))
+}
+
+/// One fingerprint hash, and the frame it anchors to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fingerprint {
+    pub hash: u64,
+    pub anchor_time: usize,
+}
+
+/// Builds the fingerprints for `frames`: finds the constellation map, then pairs each peak
+/// (as an anchor) with up to `FAN_OUT` peaks in its forward target zone.
+pub fn fingerprint(frames: &[SVector<f32, SPECTROGRAM_SIZE>]) -> Vec<Fingerprint> {
+    let peaks = find_peaks(frames);
+    let mut fingerprints = Vec::new();
+
+    for (i, anchor) in peaks.iter().enumerate() {
+        let targets = peaks[i + 1..].iter()
+            .filter(|target| {
+                let dt = target.time - anchor.time;
+                dt >= TARGET_ZONE_MIN_DT && dt <= TARGET_ZONE_MAX_DT
+            })
+            .take(FAN_OUT);
+
+        for target in targets {
+            fingerprints.push(Fingerprint {
+                hash: hash_pair(anchor, target),
+                anchor_time: anchor.time,
+            });
+        }
+    }
+
+    fingerprints
+}
+
+/// An in-memory index of known tracks' fingerprints, for recognizing which one a query
+/// (typically a few seconds of live-captured audio) is from.
+pub struct FingerprintIndex {
+    index: HashMap<u64, Vec<(u64, usize)>>,
+}
+
+impl FingerprintIndex {
+    pub fn new() -> Self {
+        Self { index: HashMap::new() }
+    }
+
+    /// Fingerprints `frames` and adds every hash to the index under `track_id`.
+    pub fn add_track(&mut self, track_id: u64, frames: &[SVector<f32, SPECTROGRAM_SIZE>]) {
+        for fp in fingerprint(frames) {
+            self.index.entry(fp.hash).or_default().push((track_id, fp.anchor_time));
+        }
+    }
+
+    /// Matches `frames` against the index: accumulates a histogram, per track, of
+    /// `db_anchor_time - query_anchor_time` offsets across every matching hash, then returns the
+    /// track whose histogram has the largest single-offset cluster (i.e. whose matches agree
+    /// most consistently on where the query sits within that track), along with how many
+    /// fingerprints support that offset. Returns `None` if no hash in the query matched
+    /// anything in the index.
+    pub fn match_track(&self, frames: &[SVector<f32, SPECTROGRAM_SIZE>]) -> Option<(u64, usize)> {
+        let mut offset_histogram: HashMap<(u64, i64), usize> = HashMap::new();
+
+        for fp in fingerprint(frames) {
+            if let Some(hits) = self.index.get(&fp.hash) {
+                for &(track_id, db_anchor_time) in hits {
+                    let offset = db_anchor_time as i64 - fp.anchor_time as i64;
+                    *offset_histogram.entry((track_id, offset)).or_insert(0) += 1;
+                }
+            }
+        }
+
+        offset_histogram.into_iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|((track_id, _), count)| (track_id, count))
+    }
+}
+
+impl Default for FingerprintIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_with_peak(freqs_and_magnitudes: &[(usize, f32)]) -> SVector<f32, SPECTROGRAM_SIZE> {
+        let mut data = [0_f32; SPECTROGRAM_SIZE];
+        for &(freq, magnitude) in freqs_and_magnitudes {
+            data[freq] = magnitude;
+        }
+        SVector::from(data)
+    }
+
+    #[test]
+    fn test_find_peaks_isolated_impulse() {
+        let frames: Vec<_> = (0..20).map(|t| {
+            if t == 10 {
+                frame_with_peak(&[(100, 1.)])
+            } else {
+                frame_with_peak(&[])
+            }
+        }).collect();
+
+        let peaks = find_peaks(&frames);
+        assert_eq!(peaks, vec![Peak { time: 10, freq: 100, magnitude: 1. }]);
+    }
+
+    #[test]
+    fn test_find_peaks_prunes_to_strongest_per_frame() {
+        let magnitudes: Vec<(usize, f32)> = (0..10).map(|i| (i * 50, 1. + i as f32)).collect();
+        let frames = vec![frame_with_peak(&magnitudes)];
+
+        let peaks = find_peaks(&frames);
+        assert_eq!(peaks.len(), PEAKS_PER_FRAME);
+        // The survivors should be the PEAKS_PER_FRAME strongest, i.e. the highest-index ones.
+        for peak in &peaks {
+            assert!(peak.magnitude >= 1. + (10 - PEAKS_PER_FRAME) as f32);
+        }
+    }
+
+    #[test]
+    fn test_hash_pair_packs_and_is_order_sensitive() {
+        let anchor = Peak { time: 10, freq: 5, magnitude: 1. };
+        let target = Peak { time: 15, freq: 7, magnitude: 1. };
+        let hash = hash_pair(&anchor, &target);
+
+        let dt = hash & ((1 << DT_BITS) - 1);
+        let freq_target = (hash >> DT_BITS) & ((1 << FREQ_BITS) - 1);
+        let freq_anchor = hash >> (FREQ_BITS + DT_BITS);
+
+        assert_eq!(dt, 5);
+        assert_eq!(freq_target, 7);
+        assert_eq!(freq_anchor, 5);
+    }
+
+    #[test]
+    fn test_fingerprint_pairs_anchors_with_forward_targets() {
+        let mut frames = vec![frame_with_peak(&[]); 30];
+        frames[10] = frame_with_peak(&[(100, 1.)]);
+        frames[15] = frame_with_peak(&[(200, 1.)]);
+
+        let fingerprints = fingerprint(&frames);
+        assert_eq!(fingerprints.len(), 1);
+        assert_eq!(fingerprints[0].anchor_time, 10);
+        assert_eq!(fingerprints[0].hash, hash_pair(
+            &Peak { time: 10, freq: 100, magnitude: 1. },
+            &Peak { time: 15, freq: 200, magnitude: 1. },
+        ));
+    }
+
+    #[test]
+    fn test_fingerprint_index_matches_consistent_offset() {
+        let mut db_frames = vec![frame_with_peak(&[]); 60];
+        db_frames[20] = frame_with_peak(&[(100, 1.)]);
+        db_frames[25] = frame_with_peak(&[(200, 1.)]);
+        db_frames[40] = frame_with_peak(&[(150, 1.)]);
+        db_frames[45] = frame_with_peak(&[(250, 1.)]);
+
+        let mut index = FingerprintIndex::new();
+        index.add_track(42, &db_frames);
+
+        // A query clipped from the middle of the track, starting 15 frames into it: every one
+        // of its 6 fingerprints (4 peaks paired forward: 3 + 2 + 1) should agree on a +15
+        // offset back into the track.
+        let query_frames = db_frames[15..].to_vec();
+
+        let result = index.match_track(&query_frames);
+        assert_eq!(result, Some((42, 6)));
+    }
+
+    #[test]
+    fn test_fingerprint_index_no_match() {
+        let db_frames = vec![frame_with_peak(&[(100, 1.)]); 1];
+        let mut index = FingerprintIndex::new();
+        index.add_track(1, &db_frames);
+
+        let query_frames = vec![frame_with_peak(&[]); 1];
+        assert_eq!(index.match_track(&query_frames), None);
+    }
+}
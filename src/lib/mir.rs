@@ -2,11 +2,105 @@ use crate::beat_tracking::{BeatTracker, SAMPLE_RATE};
 use cpal;
 use cpal::traits::DeviceTrait;
 use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::path::PathBuf;
+use std::thread;
+use std::fs::File;
 use std::time;
 
 const MAX_TIME: f32 = 64.;
-// Anticipate beats by this many seconds
-const LATENCY_COMPENSATION: f32 = 0.07;
+// Fallback lookahead used until the audio thread has reported a real stream latency
+const DEFAULT_LATENCY_COMPENSATION: f32 = 0.07;
+
+// Default envelope follower time constants for the low/mid/high/level smoothing,
+// chosen to snap onto transients quickly but decay slowly enough to avoid visual strobing
+const DEFAULT_TAU_ATTACK: f32 = 0.005;
+const DEFAULT_TAU_DECAY: f32 = 0.15;
+
+// How many chunks of captured audio the writer thread is allowed to lag behind by
+const RECORDING_BUFFER_SIZE: usize = 64;
+
+/// The crossover frequencies (in Hz) used to split the spectrogram
+/// into low/mid/high bands in `MusicInfo`.
+/// Defaults roughly follow the usual "bass/mid/treble" split used in visual patches.
+#[derive(Clone, Copy, Debug)]
+pub struct FrequencyBands {
+    pub low: (f32, f32),
+    pub mid: (f32, f32),
+    pub high: (f32, f32),
+}
+
+impl Default for FrequencyBands {
+    fn default() -> Self {
+        FrequencyBands {
+            low: (20., 250.),
+            mid: (250., 4000.),
+            high: (4000., 20000.),
+        }
+    }
+}
+
+/// Returns the frequency, in Hz, that spectrogram bin `k` (of `n` total bins) is centered on,
+/// given the convention that the spectrogram spans from 0 Hz to the Nyquist frequency.
+fn bin2freq(k: usize, n: usize) -> f32 {
+    k as f32 * SAMPLE_RATE as f32 / (2. * n as f32)
+}
+
+/// Computes the RMS energy of the bins of `spectrogram` whose center frequency
+/// falls within `[f_lo, f_hi)`.
+fn band_energy(spectrogram: &[f32], f_lo: f32, f_hi: f32) -> f32 {
+    let n = spectrogram.len();
+    let mut sum_sq = 0_f32;
+    let mut count = 0_u32;
+    for (k, &mag) in spectrogram.iter().enumerate() {
+        let freq = bin2freq(k, n);
+        if freq >= f_lo && freq < f_hi {
+            sum_sq += mag * mag;
+            count += 1;
+        }
+    }
+    if count == 0 {
+        0.
+    } else {
+        (sum_sq / count as f32).sqrt()
+    }
+}
+
+/// Computes the RMS energy over the entire spectrogram.
+fn total_rms(spectrogram: &[f32]) -> f32 {
+    if spectrogram.is_empty() {
+        return 0.;
+    }
+    let sum_sq: f32 = spectrogram.iter().map(|&x| x * x).sum();
+    (sum_sq / spectrogram.len() as f32).sqrt()
+}
+
+/// A one-pole exponential envelope follower with separate attack/decay time constants,
+/// used to smooth the jittery frame-to-frame `low`/`mid`/`high`/`level` values
+/// without adding the fixed latency a moving-average filter would.
+#[derive(Clone, Copy, Debug)]
+struct EnvelopeFollower {
+    y: f32,
+    raw: f32,
+    tau_a: f32,
+    tau_d: f32,
+}
+
+impl EnvelopeFollower {
+    fn new(tau_a: f32, tau_d: f32) -> Self {
+        EnvelopeFollower { y: 0., raw: 0., tau_a, tau_d }
+    }
+
+    /// Advances the follower by `dt` seconds towards the new instantaneous value `x`,
+    /// using `tau_a` if the signal is rising or `tau_d` if it's falling.
+    fn update(&mut self, x: f32, dt: f32) -> f32 {
+        self.raw = x;
+        let tau = if x > self.y { self.tau_a } else { self.tau_d };
+        let coeff = 1. - (-dt / tau).exp();
+        self.y += coeff * (x - self.y);
+        self.y
+    }
+}
 
 /// A Mir (Music information retrieval) object
 /// handles listening to the music via the system audio
@@ -22,6 +116,14 @@ pub struct Mir {
     _stream: cpal::Stream,
     receiver: mpsc::Receiver<Update>,
     last_update: Update,
+    bands: FrequencyBands,
+    // Shared with the audio thread: when Some, the analyzed mono signal is
+    // tee'd off to the writer thread that owns the other end of the channel.
+    recording: Arc<Mutex<Option<mpsc::SyncSender<Vec<i16>>>>>,
+    // Envelope followers smoothing the jittery frame-to-frame low/mid/high/level values;
+    // kept as a fixed array in that order so set_envelope() can update all four at once.
+    envelopes: [EnvelopeFollower; 4],
+    last_poll: time::Instant,
 }
 
 /// Updates sent over a queue
@@ -41,11 +143,16 @@ struct Update {
     t_ref: f32, // reference t measured in beats
     tempo: f32, // beats per second
 
-    // For computing the audio levels
-    low: f32,
-    mid: f32,
-    high: f32,
-    level: f32,
+    // How far to look ahead of wall_ref when computing t(),
+    // derived from cpal's reported capture-to-callback latency
+    // rather than a fixed guess, so beat phase stays locked
+    // across devices with different buffer sizes
+    latency_compensation: f32,
+
+    // The full spectrogram magnitude for this update,
+    // from which low/mid/high/level are derived at poll() time
+    // (kept as an Arc so cloning an Update for the channel send doesn't copy the data)
+    spectrogram: Arc<[f32]>,
 }
 
 impl Update {
@@ -65,30 +172,62 @@ pub struct MusicInfo {
     pub mid: f32,
     pub high: f32,
     pub level: f32,
-    // TODO: send full spectrogram
+    pub spectrogram: Arc<[f32]>,
 }
 
 impl Mir {
+    /// Convenience wrapper around `with_device` that grabs the host's default output device,
+    /// which is typically the right choice on hosts (WASAPI shared mode, etc.)
+    /// that expose loopback capture of what's currently playing as an "input" on the output device.
     pub fn new() -> Self {
+        use cpal::traits::HostTrait;
+        let host = cpal::default_host();
+        let device = host.default_output_device().expect("no output device available");
+        Self::with_device(device)
+    }
+
+    /// Lists the devices that can plausibly be used with `with_device`,
+    /// paired with a human-readable name suitable for a UI combo box
+    /// (mirroring how `ScreenOutputNodeTile` offers a combo box of `available_screens`).
+    /// This includes both input devices (e.g. a physical microphone or line-in)
+    /// and output devices (which, on many hosts, also expose loopback capture).
+    pub fn enumerate_inputs() -> Vec<(String, cpal::Device)> {
+        use cpal::traits::HostTrait;
+        let host = cpal::default_host();
+
+        let input_devices = host.input_devices().into_iter().flatten();
+        let output_devices = host.output_devices().into_iter().flatten();
+
+        input_devices
+            .chain(output_devices)
+            .filter_map(|device| {
+                let name = device.name().ok()?;
+                Some((name, device))
+            })
+            .collect()
+    }
+
+    /// Constructs a Mir that captures audio from the given device,
+    /// which may be a dedicated input device, a virtual loopback device
+    /// (BlackHole, VB-Cable, WASAPI loopback, ...), or an output device
+    /// on hosts that expose loopback capture through it.
+    pub fn with_device(device: cpal::Device) -> Self {
         // Make a new beat tracker
         let mut bt = BeatTracker::new();
 
         // Make a communication channel to communicate with the audio thread
         const MESSAGE_BUFFER_SIZE: usize = 16;
-        let (sender, receiver) = mpsc::sync_channel(MESSAGE_BUFFER_SIZE); 
-
-        // Set up system audio
-        use cpal::traits::HostTrait;
-        let host = cpal::default_host();
-        let device = host.default_output_device().expect("no output device available");
+        let (sender, receiver) = mpsc::sync_channel(MESSAGE_BUFFER_SIZE);
 
         const MIN_USEFUL_BUFFER_SIZE: cpal::FrameCount = 256; // Lower actually would be useful, but CPAL lies about the min size, so this ought to be safe
         const SAMPLE_RATE_CPAL: cpal::SampleRate = cpal::SampleRate(SAMPLE_RATE as u32);
         let config_range = device.supported_input_configs()
             .expect("error while querying configs")
-            .filter(|config| 
+            .filter(|config|
                 (config.sample_format() == cpal::SampleFormat::I16
-                || config.sample_format() == cpal::SampleFormat::U16)
+                || config.sample_format() == cpal::SampleFormat::U16
+                || config.sample_format() == cpal::SampleFormat::F32
+                || config.sample_format() == cpal::SampleFormat::F64)
                 && SAMPLE_RATE_CPAL >= config.min_sample_rate()
                 && SAMPLE_RATE_CPAL <= config.max_sample_rate()
                 && match *config.buffer_size() {
@@ -120,13 +259,29 @@ impl Mir {
             wall_ref: time::Instant::now(),
             t_ref: 0.,
             tempo: DEFAULT_BPM / 60.,
-            low: 0.,
-            mid: 0.,
-            high: 0.,
-            level: 0.,
+            latency_compensation: DEFAULT_LATENCY_COMPENSATION,
+            spectrogram: Arc::from(Vec::new()),
         };
 
-        let mut process_audio_i16_mono = move |data: &[i16]| {
+        let recording: Arc<Mutex<Option<mpsc::SyncSender<Vec<i16>>>>> = Arc::new(Mutex::new(None));
+        let recording_for_callback = recording.clone();
+
+        let mut process_audio_i16_mono = move |data: &[i16], timestamp: cpal::InputStreamTimestamp| {
+            // Tee the analyzed mono signal off to the recorder, if one is running.
+            // This never blocks: try_send drops the chunk (rather than stalling the audio thread)
+            // if the writer thread is somehow falling behind.
+            if let Some(tx) = recording_for_callback.lock().unwrap().as_ref() {
+                let _ = tx.try_send(data.to_vec());
+            }
+
+            // cpal's `capture` and `callback` instants live on the same clock,
+            // so their difference is exactly how stale this callback's samples are
+            // (buffering/driver latency between when the mic captured them and now).
+            // We can't do arithmetic between a StreamInstant and a std::time::Instant directly,
+            // so we anchor `capture` onto our wall clock by subtracting that same latency from "now".
+            let capture_latency = timestamp.callback.duration_since(&timestamp.capture).unwrap_or_default();
+            let capture_wall = time::Instant::now().checked_sub(capture_latency).unwrap_or_else(time::Instant::now);
+
             // Reduce all of the returned results into just the most recent
             // Typically; only 0 or 1 results are returned per audio frame,
             // but we do this reduction just to be safe,
@@ -141,14 +296,14 @@ impl Mir {
             // If we detected a beat, recompute the linear parameters for t
             if beat {
                 // In computing the new line, we want to preserve continuity;
-                // i.e. we want to pivot our line about the current point (wall clock time, current t in beats)
-                // So, we set wall_ref to right now, and t_ref to t(wall_ref)
-                let wall_ref = time::Instant::now();
+                // i.e. we want to pivot our line about the current point (sample-capture time, current t in beats)
+                // So, we set wall_ref to the capture time, and t_ref to t(wall_ref)
+                let wall_ref = capture_wall;
                 let t_ref = update.t(wall_ref);
 
                 // Now we just have one remaining parameter to set: the slope (aka tempo)
                 // We set the slope of the line so that it intersects the point
-                // (expected wall clock time of next beat, current integer beat + 1)
+                // (expected capture time of next beat, current integer beat + 1)
 
                 // Inter-arrival time of the last two beats, in seconds
                 let last_beat_wall_period = (wall_ref - update.wall_ref).as_secs_f32();
@@ -167,11 +322,13 @@ impl Mir {
                 update.tempo = tempo;
             }
 
-            // For now, simply set lows, mids, and highs to random spectrogram buckets
-            update.low = spectrogram[2];
-            update.mid = spectrogram[100];
-            update.high = spectrogram[800];
-            update.level = update.mid;
+            // Anticipate beats by the actual reported capture latency instead of a fixed guess,
+            // so phase stays locked across devices with different buffer sizes.
+            update.latency_compensation = capture_latency.as_secs_f32();
+
+            // Hand the full spectrogram off; low/mid/high/level are derived from it at poll() time,
+            // since the band edges are user-settable and shouldn't require restarting the audio thread.
+            update.spectrogram = Arc::from(spectrogram.as_slice());
 
             // Send an update back to the main thread
             if let Err(err) = sender.try_send(update.clone()) {
@@ -190,39 +347,45 @@ impl Mir {
             println!("MIR: audio stream error: {:?}", err)
         };
 
-        let stream = match (config_range.sample_format(), config.channels) {
-                (cpal::SampleFormat::I16, 1) => device.build_input_stream(
-                    &config,
-                    move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                        process_audio_i16_mono(data);
-                    },
-                    process_error,
-                ),
-                (cpal::SampleFormat::I16, 2) => device.build_input_stream(
-                    &config,
-                    move |data: &[i16], _: &cpal::InputCallbackInfo| {
-                        let data: Vec<i16> = data.chunks(2).map(|pair| ((pair[0] as i32 + pair[1] as i32) / 2) as i16).collect();
-                        process_audio_i16_mono(&data);
-                    },
-                    process_error,
-                ),
-                (cpal::SampleFormat::U16, 1) => device.build_input_stream(
-                    &config,
-                    move |data: &[u16], _: &cpal::InputCallbackInfo| {
-                        let data: Vec<i16> = data.iter().map(|&x| ((x as i32) - 32768) as i16).collect();
-                        process_audio_i16_mono(&data);
-                    },
-                    process_error,
-                ),
-                (cpal::SampleFormat::U16, 2) => device.build_input_stream(
-                    &config,
-                    move |data: &[u16], _: &cpal::InputCallbackInfo| {
-                        let data: Vec<i16> = data.chunks(2).map(|pair| ((pair[0] as i32 + pair[1] as i32) / 2 - 32768) as i16).collect();
-                        process_audio_i16_mono(&data);
-                    },
-                    process_error,
-                ),
-                _ => panic!("unexpected sample format or channel count")
+        // Build the input stream for any cpal::Sample type,
+        // downmixing to mono and converting to i16 before handing off to process_audio_i16_mono.
+        // This single generic helper replaces what would otherwise be
+        // one hand-written mono/stereo closure pair per sample format.
+        fn build_input_stream_generic<S, F, E>(
+            device: &cpal::Device,
+            config: &cpal::StreamConfig,
+            channels: u16,
+            mut process_audio_i16_mono: F,
+            process_error: E,
+        ) -> Result<cpal::Stream, cpal::BuildStreamError>
+        where
+            S: cpal::Sample,
+            F: FnMut(&[i16], cpal::InputStreamTimestamp) + Send + 'static,
+            E: FnMut(cpal::StreamError) + Send + 'static,
+        {
+            device.build_input_stream(
+                config,
+                move |data: &[S], info: &cpal::InputCallbackInfo| {
+                    let data: Vec<i16> = match channels {
+                        1 => data.iter().map(|&x| x.to_i16()).collect(),
+                        2 => data.chunks(2).map(|pair| {
+                            let l = pair[0].to_i16() as i32;
+                            let r = pair[1].to_i16() as i32;
+                            ((l + r) / 2) as i16
+                        }).collect(),
+                        n => panic!("unsupported channel count {}", n),
+                    };
+                    process_audio_i16_mono(&data, info.timestamp());
+                },
+                process_error,
+            )
+        }
+
+        let stream = match config_range.sample_format() {
+                cpal::SampleFormat::I16 => build_input_stream_generic::<i16, _, _>(&device, &config, config.channels, process_audio_i16_mono, process_error),
+                cpal::SampleFormat::U16 => build_input_stream_generic::<u16, _, _>(&device, &config, config.channels, process_audio_i16_mono, process_error),
+                cpal::SampleFormat::F32 => build_input_stream_generic::<f32, _, _>(&device, &config, config.channels, process_audio_i16_mono, process_error),
+                cpal::SampleFormat::F64 => build_input_stream_generic::<f64, _, _>(&device, &config, config.channels, process_audio_i16_mono, process_error),
         }.expect("failed to open input stream");
 
         Self {
@@ -232,14 +395,77 @@ impl Mir {
                 wall_ref: time::Instant::now(),
                 t_ref: 0.,
                 tempo: 0., // This will hold t at 0 until the audio thread starts up
-                low: 0.,
-                mid: 0.,
-                high: 0.,
-                level: 0.,
+                latency_compensation: DEFAULT_LATENCY_COMPENSATION,
+                spectrogram: Arc::from(Vec::new()),
             },
+            bands: FrequencyBands::default(),
+            recording,
+            envelopes: [EnvelopeFollower::new(DEFAULT_TAU_ATTACK, DEFAULT_TAU_DECAY); 4],
+            last_poll: time::Instant::now(),
         }
     }
 
+    /// Retunes the low/mid/high crossover frequencies used to compute `MusicInfo`'s audio levels.
+    /// Takes effect on the very next `poll()`, since the bands are applied to the cached spectrogram
+    /// rather than on the audio thread.
+    pub fn set_bands(&mut self, bands: FrequencyBands) {
+        self.bands = bands;
+    }
+
+    /// Begins writing the analyzed mono input signal to a 16-bit PCM WAV file at `path`,
+    /// for debugging the beat tracker offline or re-analyzing a session later.
+    /// The file is written by a dedicated thread so the audio callback never blocks on disk I/O;
+    /// any previously running recording is implicitly stopped (its file is finalized) first.
+    pub fn start_recording(&mut self, path: impl Into<PathBuf>) {
+        let path = path.into();
+        let (tx, rx) = mpsc::sync_channel::<Vec<i16>>(RECORDING_BUFFER_SIZE);
+
+        thread::spawn(move || {
+            let mut samples: Vec<i16> = Vec::new();
+            while let Ok(chunk) = rx.recv() {
+                samples.extend(chunk);
+            }
+
+            // The channel only closes once stop_recording() drops the sender, so this is
+            // where we actually have the full recording and can pay the cost of writing it out.
+            let header = wav::Header::new(wav::WAV_FORMAT_PCM, 1, SAMPLE_RATE as u32, 16);
+            match File::create(&path) {
+                Ok(mut file) => {
+                    if let Err(err) = wav::write(header, &wav::BitDepth::Sixteen(samples), &mut file) {
+                        println!("MIR: failed to write recording to {:?}: {:?}", path, err);
+                    }
+                },
+                Err(err) => {
+                    println!("MIR: failed to create recording file {:?}: {:?}", path, err);
+                },
+            }
+        });
+
+        *self.recording.lock().unwrap() = Some(tx);
+    }
+
+    /// Stops any recording started by `start_recording`, finalizing the WAV file.
+    /// Does nothing if no recording is in progress.
+    pub fn stop_recording(&mut self) {
+        *self.recording.lock().unwrap() = None;
+    }
+
+    /// Sets the attack and decay time constants (in seconds) used to smooth
+    /// `low`/`mid`/`high`/`level` in `MusicInfo`. Shorter `tau_a` makes transients snappier;
+    /// longer `tau_d` makes the decay after a transient smoother (at the cost of responsiveness).
+    pub fn set_envelope(&mut self, tau_a: f32, tau_d: f32) {
+        for envelope in self.envelopes.iter_mut() {
+            envelope.tau_a = tau_a;
+            envelope.tau_d = tau_d;
+        }
+    }
+
+    /// Returns the most recent unsmoothed (raw) `(low, mid, high, level)` values,
+    /// for users who want the instantaneous signal rather than the envelope-followed one.
+    pub fn raw(&self) -> (f32, f32, f32, f32) {
+        (self.envelopes[0].raw, self.envelopes[1].raw, self.envelopes[2].raw, self.envelopes[3].raw)
+    }
+
     pub fn poll(&mut self) -> MusicInfo {
         // Drain the receiver,
         // applying the most recent update from the audio thread
@@ -248,14 +474,26 @@ impl Mir {
             None => {},
         }
 
-        // Compute t
-        let t = self.last_update.t(time::Instant::now() + time::Duration::from_secs_f32(LATENCY_COMPENSATION));
+        // Compute t, anticipating beats by the stream's own reported capture latency
+        let lookahead = time::Duration::from_secs_f32(self.last_update.latency_compensation);
+        let t = self.last_update.t(time::Instant::now() + lookahead);
+
+        // Derive the audio levels from the cached spectrogram and the current band edges
+        let spectrogram = &self.last_update.spectrogram;
+        let low = band_energy(spectrogram, self.bands.low.0, self.bands.low.1);
+        let mid = band_energy(spectrogram, self.bands.mid.0, self.bands.mid.1);
+        let high = band_energy(spectrogram, self.bands.high.0, self.bands.high.1);
+        let level = total_rms(spectrogram);
 
-        // Simply take the most recent values for the audio levels
-        let low = self.last_update.low;
-        let mid = self.last_update.mid;
-        let high = self.last_update.high;
-        let level = self.last_update.level;
+        // Smooth each level with its own envelope follower, so visuals don't strobe
+        // from frame-to-frame jitter in the raw spectrogram energy.
+        let now = time::Instant::now();
+        let dt = (now - self.last_poll).as_secs_f32();
+        self.last_poll = now;
+        let low = self.envelopes[0].update(low, dt);
+        let mid = self.envelopes[1].update(mid, dt);
+        let high = self.envelopes[2].update(high, dt);
+        let level = self.envelopes[3].update(level, dt);
 
         MusicInfo {
             t,
@@ -263,6 +501,7 @@ impl Mir {
             mid,
             high,
             level,
+            spectrogram: spectrogram.clone(),
         }
     }
 }
@@ -0,0 +1,225 @@
+// An audio front-end that adapts arbitrary WAV input (any channel count, sample rate, and bit
+// depth) to the mono 44100 Hz PCM that `FramedSignalProcessor` hard-requires: downmix to mono,
+// then resample with windowed-sinc interpolation.
+
+use crate::beat_tracking::SAMPLE_RATE;
+use wav::BitDepth;
+
+/// Number of sinc lobes kept on each side of the interpolation point. Higher values trade
+/// compute for a sharper anti-aliasing cutoff and less passband ripple.
+const SINC_HALF_WIDTH: i64 = 8;
+
+/// The normalized sinc function, `sin(pi*x) / (pi*x)`, with the removable singularity at 0
+/// filled in with its limit.
+fn sinc(x: f32) -> f32 {
+    if x == 0. {
+        1.
+    } else {
+        (std::f32::consts::PI * x).sin() / (std::f32::consts::PI * x)
+    }
+}
+
+/// A Hann window centered at 0 and spanning `[-half_width, half_width]`, used to taper the
+/// sinc kernel to zero at its edges instead of truncating it abruptly.
+fn hann_window(x: f32, half_width: f32) -> f32 {
+    if x.abs() >= half_width {
+        0.
+    } else {
+        0.5 + 0.5 * (std::f32::consts::PI * x / half_width).cos()
+    }
+}
+
+/// Converts the samples in a WAV `BitDepth` to `f32` in the range `[-1, 1]`, regardless of
+/// whether the file was 8-bit unsigned, 16-bit signed, 24-bit signed, or 32-bit float PCM.
+/// The one exception is 16-bit `i16::MIN`, which lands just below, at `-32768 / 32767`.
+pub fn bit_depth_to_f32(data: &BitDepth) -> Vec<f32> {
+    match data {
+        BitDepth::Eight(samples) => samples.iter().map(|&s| (s as f32 - 128.) / 128.).collect(),
+        BitDepth::Sixteen(samples) => samples.iter().map(|&s| s as f32 / i16::MAX as f32).collect(),
+        BitDepth::TwentyFour(samples) => samples.iter().map(|&s| s as f32 / (1 << 23) as f32).collect(),
+        BitDepth::ThirtyTwoFloat(samples) => samples.clone(),
+        BitDepth::Empty => Vec::new(),
+    }
+}
+
+/// Downmixes interleaved multi-channel `f32` samples to mono by a weighted sum of each frame's
+/// channels. Defaults to a uniform average (`new`), but accepts a custom remix matrix
+/// (`with_weights`) for callers who want e.g. a center-channel-weighted downmix.
+pub struct ChannelRemixProcessor {
+    weights: Vec<f32>,
+}
+
+impl ChannelRemixProcessor {
+    /// A downmix that averages all `channels` equally.
+    pub fn new(channels: usize) -> Self {
+        Self::with_weights(vec![1. / channels as f32; channels])
+    }
+
+    /// A downmix with a custom per-channel weight. `weights.len()` determines the expected
+    /// channel count.
+    pub fn with_weights(weights: Vec<f32>) -> Self {
+        Self { weights }
+    }
+
+    /// Downmixes `interleaved` (frames of `self.weights.len()` channels each) to one mono
+    /// sample per frame.
+    pub fn process(&self, interleaved: &[f32]) -> Vec<f32> {
+        let channels = self.weights.len();
+        interleaved.chunks_exact(channels)
+            .map(|frame| frame.iter().zip(self.weights.iter()).map(|(x, w)| x * w).sum())
+            .collect()
+    }
+}
+
+/// Resamples a mono `f32` signal between arbitrary sample rates via windowed-sinc
+/// interpolation. Each output sample at fractional source position `p` is a weighted sum of the
+/// input samples around it: `x[floor(p)-K+1 .. floor(p)+K]`, weighted by
+/// `cutoff * sinc(cutoff*(p-n)) * hann_window(p-n)`. `cutoff` is 1 when upsampling, and scaled
+/// down to `output_rate / input_rate` when downsampling, which both lowers the sinc kernel's
+/// cutoff frequency and rescales its peak so the filter stays a unit-gain lowpass — without it,
+/// downsampling would alias frequencies above the new Nyquist rate back into the passband.
+pub struct SincResampler {
+    cutoff: f32,
+}
+
+impl SincResampler {
+    pub fn new(input_rate: usize, output_rate: usize) -> Self {
+        Self { cutoff: (output_rate as f32 / input_rate as f32).min(1.) }
+    }
+
+    pub fn resample(&self, input: &[f32], input_rate: usize, output_rate: usize) -> Vec<f32> {
+        if input.is_empty() {
+            return Vec::new();
+        }
+        let ratio = output_rate as f32 / input_rate as f32;
+        let n_out = (input.len() as f32 * ratio).round() as usize;
+        (0..n_out).map(|i| self.sample_at(input, i as f32 / ratio)).collect()
+    }
+
+    fn sample_at(&self, input: &[f32], p: f32) -> f32 {
+        let center = p.floor() as i64;
+        let mut sum = 0_f32;
+        for k in (-SINC_HALF_WIDTH + 1)..=SINC_HALF_WIDTH {
+            let n = center + k;
+            if n < 0 || n as usize >= input.len() {
+                continue;
+            }
+            let dist = p - n as f32;
+            let weight = self.cutoff * sinc(self.cutoff * dist) * hann_window(dist, SINC_HALF_WIDTH as f32);
+            sum += input[n as usize] * weight;
+        }
+        sum
+    }
+}
+
+/// Adapts arbitrary interleaved multi-channel PCM at any sample rate and bit depth to the mono
+/// 44100 Hz `i16` PCM `BeatTracker::process` expects, by downmixing (`ChannelRemixProcessor`)
+/// then resampling (`SincResampler`). Wire this in front of a `BeatTracker` to feed it
+/// real-world files directly instead of hand-matching its hard-coded format.
+pub struct AudioFrontend {
+    remix: ChannelRemixProcessor,
+    resampler: SincResampler,
+    input_rate: usize,
+}
+
+impl AudioFrontend {
+    pub fn new(channels: usize, input_rate: usize) -> Self {
+        Self {
+            remix: ChannelRemixProcessor::new(channels),
+            resampler: SincResampler::new(input_rate, SAMPLE_RATE),
+            input_rate,
+        }
+    }
+
+    pub fn with_weights(weights: Vec<f32>, input_rate: usize) -> Self {
+        Self {
+            remix: ChannelRemixProcessor::with_weights(weights),
+            resampler: SincResampler::new(input_rate, SAMPLE_RATE),
+            input_rate,
+        }
+    }
+
+    /// Converts a WAV file's header and sample data into mono `i16` PCM at `SAMPLE_RATE`,
+    /// ready for `BeatTracker::process`.
+    pub fn from_wav(header: &wav::Header, data: &BitDepth) -> Vec<i16> {
+        let frontend = Self::new(header.channel_count as usize, header.sampling_rate as usize);
+        frontend.process(&bit_depth_to_f32(data))
+    }
+
+    /// Downmixes and resamples interleaved `f32` samples (at `self.input_rate`, normalized to
+    /// `[-1, 1]`) into mono `i16` PCM at `SAMPLE_RATE`.
+    pub fn process(&self, interleaved: &[f32]) -> Vec<i16> {
+        let mono = self.remix.process(interleaved);
+        let resampled = self.resampler.resample(&mono, self.input_rate, SAMPLE_RATE);
+        resampled.iter().map(|&x| (x * i16::MAX as f32).round().clamp(i16::MIN as f32, i16::MAX as f32) as i16).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sinc() {
+        assert_eq!(sinc(0.), 1.);
+        assert!(sinc(1.).abs() < 1e-6);
+        assert!(sinc(2.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_hann_window() {
+        assert_eq!(hann_window(0., 8.), 1.);
+        assert_eq!(hann_window(8., 8.), 0.);
+        assert_eq!(hann_window(9., 8.), 0.);
+    }
+
+    #[test]
+    fn test_bit_depth_to_f32() {
+        assert_eq!(bit_depth_to_f32(&BitDepth::Sixteen(vec![0, i16::MAX, i16::MIN])), vec![0., 1., -1.0000305]);
+        assert_eq!(bit_depth_to_f32(&BitDepth::Eight(vec![0, 128, 255])), vec![-1., 0., 0.9921875]);
+        assert_eq!(bit_depth_to_f32(&BitDepth::Empty), Vec::<f32>::new());
+    }
+
+    #[test]
+    fn test_channel_remix_processor_stereo_average() {
+        let remix = ChannelRemixProcessor::new(2);
+        let mono = remix.process(&[1., 0., 0., 1., -1., 1.]);
+        assert_eq!(mono, vec![0.5, 0.5, 0.]);
+    }
+
+    #[test]
+    fn test_channel_remix_processor_custom_weights() {
+        let remix = ChannelRemixProcessor::with_weights(vec![1., 0.]);
+        let mono = remix.process(&[1., 5., 2., 9.]);
+        assert_eq!(mono, vec![1., 2.]);
+    }
+
+    #[test]
+    fn test_sinc_resampler_preserves_length_ratio() {
+        let input = vec![0_f32; 1000];
+        let resampler = SincResampler::new(44100, 22050);
+        let output = resampler.resample(&input, 44100, 22050);
+        assert_eq!(output.len(), 500);
+    }
+
+    #[test]
+    fn test_sinc_resampler_upsamples_constant_signal() {
+        let input = vec![1_f32; 100];
+        let resampler = SincResampler::new(22050, 44100);
+        let output = resampler.resample(&input, 22050, 44100);
+        // Away from the edges (where the truncated kernel has fewer taps to work with),
+        // resampling a constant signal should reproduce that constant.
+        for &sample in &output[20..output.len() - 20] {
+            assert!((sample - 1.).abs() < 1e-3, "{}", sample);
+        }
+    }
+
+    #[test]
+    fn test_audio_frontend_stereo_upsample() {
+        let frontend = AudioFrontend::new(2, 22050);
+        let interleaved: Vec<f32> = (0..200).map(|i| if i % 2 == 0 { 0.5 } else { -0.5 }).collect();
+        let out = frontend.process(&interleaved);
+        // 100 stereo frames at 22050 Hz upsampled to 44100 Hz should yield about 200 mono samples.
+        assert_eq!(out.len(), 200);
+    }
+}
@@ -0,0 +1,175 @@
+// Renders a sequence of spectrogram-like frames (the `SVector<f32, N_FILTERS>` log-filtered
+// frames from `FilteredSpectrogramProcessor`, or the raw `SVector<f32, SPECTROGRAM_SIZE>`
+// magnitudes) into a debug image: time along the x-axis, frequency/filter bins up the y-axis,
+// normalized across the whole collection to the 0-255 range. Lets developers visually sanity-check
+// the onset/beat front-end instead of eyeballing numbers dumped from `test_music`.
+
+use std::path::Path;
+use nalgebra::SVector;
+
+// A handful of (position, color) stops approximating the Viridis perceptual colormap, chosen so
+// the gradient reads as low-to-high energy without the misleading brightness jumps of a rainbow
+// map.
+const VIRIDIS_STOPS: [(f32, [u8; 3]); 5] = [
+    (0.00, [68, 1, 84]),
+    (0.25, [59, 82, 139]),
+    (0.50, [33, 145, 140]),
+    (0.75, [94, 201, 98]),
+    (1.00, [253, 231, 37]),
+];
+
+fn lerp_u8(a: u8, b: u8, t: f32) -> u8 {
+    (a as f32 + (b as f32 - a as f32) * t).round() as u8
+}
+
+/// Maps `t` in `[0, 1]` to an RGB color by linearly interpolating between the nearest two
+/// `VIRIDIS_STOPS`.
+fn viridis(t: f32) -> [u8; 3] {
+    let t = t.clamp(0., 1.);
+    for window in VIRIDIS_STOPS.windows(2) {
+        let (t0, c0) = window[0];
+        let (t1, c1) = window[1];
+        if t <= t1 {
+            let frac = if t1 > t0 { (t - t0) / (t1 - t0) } else { 0. };
+            return [
+                lerp_u8(c0[0], c1[0], frac),
+                lerp_u8(c0[1], c1[1], frac),
+                lerp_u8(c0[2], c1[2], frac),
+            ];
+        }
+    }
+    VIRIDIS_STOPS[VIRIDIS_STOPS.len() - 1].1
+}
+
+/// Which color scheme to render a spectrogram image with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Colormap {
+    /// One byte per pixel, low energy black and high energy white.
+    Grayscale,
+    /// Three bytes per pixel, a Viridis-style perceptual gradient.
+    Viridis,
+}
+
+impl Colormap {
+    fn channels(&self) -> usize {
+        match self {
+            Colormap::Grayscale => 1,
+            Colormap::Viridis => 3,
+        }
+    }
+
+    fn color_type(&self) -> image::ColorType {
+        match self {
+            Colormap::Grayscale => image::ColorType::L8,
+            Colormap::Viridis => image::ColorType::Rgb8,
+        }
+    }
+
+    /// Maps a normalized value in `[0, 1]` to this colormap's pixel bytes.
+    fn map(&self, t: f32) -> Vec<u8> {
+        match self {
+            Colormap::Grayscale => vec![(t.clamp(0., 1.) * 255.).round() as u8],
+            Colormap::Viridis => viridis(t).to_vec(),
+        }
+    }
+}
+
+/// Renders `frames` (one column per frame, `N` rows of frequency/filter bins) into a raw pixel
+/// buffer in `colormap`'s format, suitable for uploading as a texture. Values are normalized to
+/// `[0, 1]` using the min/max across every frame, so the image reflects relative energy within
+/// this collection rather than any fixed absolute scale. Frequency increases upward (row 0 of
+/// the image is the highest bin), matching how a spectrogram is conventionally drawn. Returns
+/// `(buffer, width, height)`; `(Vec::new(), 0, 0)` if `frames` is empty.
+pub fn render_buffer<const N: usize>(frames: &[SVector<f32, N>], colormap: Colormap) -> (Vec<u8>, u32, u32) {
+    if frames.is_empty() {
+        return (Vec::new(), 0, 0);
+    }
+
+    let width = frames.len();
+    let height = N;
+
+    let mut min = f32::INFINITY;
+    let mut max = f32::NEG_INFINITY;
+    for frame in frames {
+        for i in 0..N {
+            min = min.min(frame[i]);
+            max = max.max(frame[i]);
+        }
+    }
+    let range = if max > min { max - min } else { 1. };
+
+    let channels = colormap.channels();
+    let mut buffer = vec![0_u8; width * height * channels];
+    for (x, frame) in frames.iter().enumerate() {
+        for freq_bin in 0..height {
+            let value = (frame[freq_bin] - min) / range;
+            let y = height - 1 - freq_bin;
+            let pixel = colormap.map(value);
+            let offset = (y * width + x) * channels;
+            buffer[offset..offset + channels].copy_from_slice(&pixel);
+        }
+    }
+
+    (buffer, width as u32, height as u32)
+}
+
+/// Renders `frames` (as `render_buffer`) and writes the result to a PNG at `path`.
+pub fn save_png<const N: usize>(
+    frames: &[SVector<f32, N>],
+    colormap: Colormap,
+    path: impl AsRef<Path>,
+) -> image::ImageResult<()> {
+    let (buffer, width, height) = render_buffer(frames, colormap);
+    image::save_buffer(path, &buffer, width, height, colormap.color_type())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_viridis_endpoints() {
+        assert_eq!(viridis(0.), VIRIDIS_STOPS[0].1);
+        assert_eq!(viridis(1.), VIRIDIS_STOPS[VIRIDIS_STOPS.len() - 1].1);
+    }
+
+    #[test]
+    fn test_render_buffer_empty() {
+        let frames: Vec<SVector<f32, 2>> = Vec::new();
+        assert_eq!(render_buffer(&frames, Colormap::Grayscale), (Vec::new(), 0, 0));
+    }
+
+    #[test]
+    fn test_render_buffer_grayscale_normalizes_and_flips_y() {
+        // 2 frames (columns) of 2 bins (rows) each: bin 0 is the low end, bin 1 the high end.
+        let frames = vec![
+            SVector::from([0_f32, 10.]),
+            SVector::from([5_f32, 10.]),
+        ];
+        let (buffer, width, height) = render_buffer(&frames, Colormap::Grayscale);
+        assert_eq!((width, height), (2, 2));
+
+        // Global min is 0, max is 10. Frequency increases upward, so row 0 (top) holds bin 1
+        // and row 1 (bottom) holds bin 0.
+        assert_eq!(buffer[0], 255); // row 0 (bin 1), column 0: (10-0)/10 -> 255
+        assert_eq!(buffer[1], 255); // row 0 (bin 1), column 1: (10-0)/10 -> 255
+        assert_eq!(buffer[2], 0);   // row 1 (bin 0), column 0: (0-0)/10 -> 0
+        assert_eq!(buffer[3], 128); // row 1 (bin 0), column 1: (5-0)/10 -> 0.5 -> 128
+    }
+
+    #[test]
+    fn test_render_buffer_viridis_channel_count() {
+        let frames = vec![SVector::from([0_f32, 1.])];
+        let (buffer, width, height) = render_buffer(&frames, Colormap::Viridis);
+        assert_eq!(buffer.len(), (width * height) as usize * 3);
+    }
+
+    #[test]
+    fn test_render_buffer_constant_frames_maps_to_zero() {
+        // Every value equal means `range` would be zero; the normalization should fall back to
+        // mapping everything to the bottom of the colormap instead of dividing by zero.
+        let frames = vec![SVector::from([3_f32, 3.]), SVector::from([3_f32, 3.])];
+        let (buffer, ..) = render_buffer(&frames, Colormap::Grayscale);
+        assert!(buffer.iter().all(|&b| b == 0));
+    }
+}
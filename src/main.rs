@@ -1,15 +1,30 @@
-use radiance::{DefaultContext, EffectNode, EffectNodeArguments};
+use radiance::{DefaultContext, EffectNode, EffectNodeArguments, ShaderWatcher};
 use winit::{
     event::*,
     event_loop::{EventLoop, ControlFlow},
     window::{Window, WindowBuilder},
 };
-use futures::executor::block_on;
 use imgui::*;
 use radiance::imgui_wgpu;
 use std::rc::Rc;
+use shaderc;
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+// A second, retained-layout UI on top of the same `DefaultContext`/`EffectNode` graph, built
+// with egui instead of imgui. Lives side by side with the imgui path below rather than
+// replacing it: `cargo run --features egui-ui` picks this one, a plain `cargo run` keeps the
+// existing immediate-mode imgui demo.
+#[cfg(feature = "egui-ui")]
+use radiance::GraphNodeId;
+#[cfg(feature = "egui-ui")]
+use std::collections::HashMap;
 
 struct State {
+    // Kept around (rather than dropped at the end of `new`) so a second surface - the
+    // projector output window - can be created against the same GPU later.
+    pub instance: wgpu::Instance,
     pub surface: wgpu::Surface,
     pub device: wgpu::Device,
     pub queue: wgpu::Queue,
@@ -37,7 +52,15 @@ impl State {
         let (device, queue) = adapter
             .request_device(
                 &wgpu::DeviceDescriptor {
-                    features: wgpu::Features::SAMPLED_TEXTURE_BINDING_ARRAY, // Need to remove for web port
+                    // SAMPLED_TEXTURE_BINDING_ARRAY backs `EffectNode`'s iInputsTex[]/iPassTex[]
+                    // arrays, but the browser WebGPU backend doesn't expose it yet, so it isn't
+                    // requested on wasm32. `EffectNode` has no one-texture-at-a-time binding
+                    // path: its layouts still declare those arrays there.
+                    features: if cfg!(target_arch = "wasm32") {
+                        wgpu::Features::empty()
+                    } else {
+                        wgpu::Features::SAMPLED_TEXTURE_BINDING_ARRAY
+                    },
                     limits: wgpu::Limits::default(),
                     shader_validation: true,
                 },
@@ -56,6 +79,7 @@ impl State {
         let swap_chain = device.create_swap_chain(&surface, &sc_desc);
 
         Self {
+            instance,
             surface,
             device,
             queue,
@@ -66,6 +90,12 @@ impl State {
     }
 
     fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        // Browsers fire a resize (and an accompanying ScaleFactorChanged) with a zero-sized
+        // canvas while the page is still laying out; a zero-sized swap chain is invalid, so
+        // just ignore those instead of recreating one.
+        if new_size.width == 0 || new_size.height == 0 {
+            return;
+        }
         self.size = new_size;
         self.sc_desc.width = new_size.width;
         self.sc_desc.height = new_size.height;
@@ -73,7 +103,223 @@ impl State {
     }
 }
 
-fn render_imgui(winit_window: &Window, state: &mut State, imgui: &mut imgui::Context, platform: &mut imgui_winit_support::WinitPlatform, renderer: &mut imgui_wgpu::Renderer, purple_tex_id: TextureId) {
+// A minimal "sample this texture over a fullscreen triangle strip" fragment shader, reusing
+// the same precompiled vertex stage (`effect_vertex.spv`) every `EffectNode` render pass
+// already draws with, rather than introducing a second vertex shader just for this.
+const BLIT_FRAGMENT_SHADER_SOURCE: &str = "
+#version 450
+layout(location = 0) in vec2 uv;
+layout(location = 0) out vec4 outColor;
+layout(set = 0, binding = 0) uniform texture2D iTex;
+layout(set = 0, binding = 1) uniform sampler iSampler;
+void main() {
+    outColor = texture(sampler2D(iTex, iSampler), uv);
+}
+";
+
+/// A second, borderless (optionally fullscreen) window that shows only the live chain's
+/// composited output, with no imgui overlay - meant to be sent to a projector or a second
+/// display while the primary window keeps the performer-facing UI.
+struct ProjectorWindow {
+    window: Window,
+    surface: wgpu::Surface,
+    sc_desc: wgpu::SwapChainDescriptor,
+    swap_chain: wgpu::SwapChain,
+    bind_group_layout: wgpu::BindGroupLayout,
+    render_pipeline: wgpu::RenderPipeline,
+    sampler: wgpu::Sampler,
+}
+
+impl ProjectorWindow {
+    fn new(event_loop: &winit::event_loop::EventLoopWindowTarget<()>, instance: &wgpu::Instance, device: &wgpu::Device) -> Self {
+        // Prefer a second monitor if one is plugged in, so the projector output doesn't just
+        // land on top of the primary window on a single-display machine.
+        let monitor = event_loop.available_monitors().nth(1).or_else(|| event_loop.primary_monitor());
+
+        let window = WindowBuilder::new()
+            .with_decorations(false)
+            .with_fullscreen(monitor.map(winit::window::Fullscreen::Borderless))
+            .build(event_loop)
+            .unwrap();
+
+        let surface = unsafe { instance.create_surface(&window) };
+        let size = window.inner_size();
+        let sc_desc = wgpu::SwapChainDescriptor {
+            usage: wgpu::TextureUsage::OUTPUT_ATTACHMENT,
+            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+        let swap_chain = device.create_swap_chain(&surface, &sc_desc);
+
+        let vs_module = device.create_shader_module(wgpu::include_spirv!(concat!(env!("OUT_DIR"), "/effect_vertex.spv")));
+        let mut compiler = shaderc::Compiler::new().unwrap();
+        let fs_binary = compiler
+            .compile_into_spirv(BLIT_FRAGMENT_SHADER_SOURCE, shaderc::ShaderKind::Fragment, "projector blit", "main", None)
+            .expect("failed to compile the built-in projector blit shader");
+        let fs_module = device.create_shader_module(wgpu::util::make_spirv(fs_binary.as_binary_u8()));
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::SampledTexture {
+                        multisampled: false,
+                        dimension: wgpu::TextureViewDimension::D2,
+                        component_type: wgpu::TextureComponentType::Uint,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler { comparison: false },
+                    count: None,
+                },
+            ],
+            label: Some("projector blit bind group layout"),
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Projector Blit Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Projector Blit Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex_stage: wgpu::ProgrammableStageDescriptor {
+                module: &vs_module,
+                entry_point: "main",
+            },
+            fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+                module: &fs_module,
+                entry_point: "main",
+            }),
+            rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+                front_face: wgpu::FrontFace::Cw,
+                cull_mode: wgpu::CullMode::Back,
+                depth_bias: 0,
+                depth_bias_slope_scale: 0.0,
+                depth_bias_clamp: 0.0,
+                clamp_depth: false,
+            }),
+            color_states: &[wgpu::ColorStateDescriptor {
+                format: sc_desc.format,
+                color_blend: wgpu::BlendDescriptor::REPLACE,
+                alpha_blend: wgpu::BlendDescriptor::REPLACE,
+                write_mask: wgpu::ColorWrite::ALL,
+            }],
+            primitive_topology: wgpu::PrimitiveTopology::TriangleStrip,
+            depth_stencil_state: None,
+            vertex_state: wgpu::VertexStateDescriptor {
+                index_format: wgpu::IndexFormat::Uint16,
+                vertex_buffers: &[],
+            },
+            sample_count: 1,
+            sample_mask: !0,
+            alpha_to_coverage_enabled: false,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        });
+
+        ProjectorWindow {
+            window,
+            surface,
+            sc_desc,
+            swap_chain,
+            bind_group_layout,
+            render_pipeline,
+            sampler,
+        }
+    }
+
+    fn id(&self) -> winit::window::WindowId {
+        self.window.id()
+    }
+
+    fn resize(&mut self, device: &wgpu::Device, new_size: winit::dpi::PhysicalSize<u32>) {
+        if new_size.width == 0 || new_size.height == 0 {
+            return;
+        }
+        self.sc_desc.width = new_size.width;
+        self.sc_desc.height = new_size.height;
+        self.swap_chain = device.create_swap_chain(&self.surface, &self.sc_desc);
+    }
+
+    /// Toggles between borderless-fullscreen (the performance-facing mode) and a normal
+    /// bordered window (for repositioning the projector output before a show).
+    fn toggle_fullscreen(&mut self) {
+        match self.window.fullscreen() {
+            Some(_) => self.window.set_fullscreen(None),
+            None => {
+                let monitor = self.window.current_monitor();
+                self.window.set_fullscreen(monitor.map(winit::window::Fullscreen::Borderless));
+            }
+        }
+    }
+
+    fn render(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, tex: &Rc<radiance::Texture>) {
+        let frame = match self.swap_chain.get_current_frame() {
+            Ok(frame) => frame,
+            Err(e) => {
+                eprintln!("Error getting projector frame: {:?}", e);
+                return;
+            }
+        }
+        .output;
+
+        let tex_view = tex.texture.create_view(&Default::default());
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&tex_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&self.sampler),
+                },
+            ],
+            label: Some("projector blit bind group"),
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Projector Encoder"),
+        });
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                    attachment: &frame.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color { r: 0., g: 0., b: 0., a: 1. }),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+            pass.set_pipeline(&self.render_pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.draw(0..4, 0..1);
+        }
+        queue.submit(Some(encoder.finish()));
+    }
+}
+
+fn render_imgui(winit_window: &Window, state: &mut State, imgui: &mut imgui::Context, platform: &mut imgui_winit_support::WinitPlatform, renderer: &mut imgui_wgpu::Renderer, purple_tex_id: TextureId, shader_error: Option<&str>) {
     // Update the UI
     platform
         .prepare_frame(imgui.io_mut(), winit_window)
@@ -97,6 +343,12 @@ fn render_imgui(winit_window: &Window, state: &mut State, imgui: &mut imgui::Con
                 ));
                 ui.separator();
                 imgui::Image::new(purple_tex_id, [100.0, 100.0]).build(&ui);
+                // Surface a failed hot-reload next to the node instead of letting it
+                // go dark; the pipeline from before the edit keeps rendering above.
+                if let Some(err) = shader_error {
+                    ui.separator();
+                    ui.text_colored([1.0, 0.4, 0.4, 1.0], im_str!("Shader error: {}", err));
+                }
             });
     }
 
@@ -137,21 +389,36 @@ fn render_imgui(winit_window: &Window, state: &mut State, imgui: &mut imgui::Con
     state.queue.submit(Some(encoder.finish()));
 }
 
-fn main() {
+/// Builds the window, wgpu/imgui state and runs the event loop. Shared between the native
+/// entry point (which blocks on it) and the wasm32 entry point (which spawns it as a local
+/// future, since a browser tab can never block its main thread waiting on the GPU).
+async fn run() {
     // Set up winit
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new()
         .build(&event_loop)
         .unwrap();
 
+    // Attach the window to a canvas in the page: winit creates an offscreen canvas on wasm32
+    // that nothing displays until it's parented into the DOM ourselves.
+    #[cfg(target_arch = "wasm32")]
+    {
+        use winit::platform::web::WindowExtWebSys;
+        web_sys::window()
+            .and_then(|win| win.document())
+            .and_then(|doc| doc.body())
+            .and_then(|body| body.append_child(&web_sys::Element::from(window.canvas())).ok())
+            .expect("couldn't append canvas to document body");
+    }
+
     // Set up wgpu
-    let mut state: State = block_on(State::new(&window));
+    let mut state: State = State::new(&window).await;
 
     // Set up imgui
     let mut imgui = imgui::Context::create();
     let mut platform = imgui_winit_support::WinitPlatform::init(&mut imgui);
     platform.attach_window(
-        imgui.io_mut(), 
+        imgui.io_mut(),
         &window,
         imgui_winit_support::HiDpiMode::Default,
     );
@@ -184,9 +451,20 @@ fn main() {
     let chain = ctx.chain(test_chain_id).unwrap();
     let mut paint_state = effect_node.new_paint_state(chain, &state.device);
 
+    // Watching the shader directory for live edits only makes sense where there's a
+    // filesystem to watch; a browser build ships its shaders baked in, so there's nothing
+    // to notice changing underneath it.
+    #[cfg(not(target_arch = "wasm32"))]
+    let shader_watcher = ShaderWatcher::new("shaders").expect("failed to watch shader directory");
+
     let mut purple_tex_id = None;
+    let mut last_frame = std::time::Instant::now();
+    // The projector output window is created on demand (`P` toggles it) rather than always
+    // opened, so running with a single display doesn't spawn a second window nobody asked for.
+    let mut projector: Option<ProjectorWindow> = None;
+    let mut last_tex: Option<Rc<radiance::Texture>> = None;
 
-    event_loop.run(move |event, _, control_flow| {
+    event_loop.run(move |event, event_loop_target, control_flow| {
         platform.handle_event(imgui.io_mut(), &window, &event);
         match event {
             Event::WindowEvent {
@@ -202,6 +480,36 @@ fn main() {
                                 virtual_keycode: Some(VirtualKeyCode::Escape),
                                 ..
                             } => *control_flow = ControlFlow::Exit,
+                            // Tap tempo: tap this in time with the music and the BPM implied by
+                            // the gap between taps gets adopted, the same way a DJ controller's
+                            // tap-tempo button works.
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::Space),
+                                ..
+                            } => ctx.tap_tempo(),
+                            // Open or close the projector output window.
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::P),
+                                ..
+                            } => {
+                                projector = match projector.take() {
+                                    Some(_) => None,
+                                    None => Some(ProjectorWindow::new(event_loop_target, &state.instance, &state.device)),
+                                };
+                            }
+                            // Toggle the projector window between borderless-fullscreen and
+                            // a normal bordered window, for repositioning before a show.
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::F),
+                                ..
+                            } => {
+                                if let Some(projector) = &mut projector {
+                                    projector.toggle_fullscreen();
+                                }
+                            }
                             _ => {}
                         },
                         WindowEvent::Resized(physical_size) => {
@@ -215,7 +523,32 @@ fn main() {
                     }
                 //}
             }
-            Event::RedrawRequested(_) => {
+            Event::WindowEvent {
+                ref event,
+                window_id,
+            } if Some(window_id) == projector.as_ref().map(ProjectorWindow::id) => {
+                match event {
+                    // Closing the projector window just stops projecting; the performer-facing
+                    // primary window (and the app) keeps running.
+                    WindowEvent::CloseRequested => projector = None,
+                    WindowEvent::Resized(physical_size) => {
+                        if let Some(projector) = &mut projector {
+                            projector.resize(&state.device, *physical_size);
+                        }
+                    }
+                    WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+                        if let Some(projector) = &mut projector {
+                            projector.resize(&state.device, **new_inner_size);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::RedrawRequested(window_id) if window_id == window.id() => {
+                let now = std::time::Instant::now();
+                imgui.io_mut().update_delta_time(now - last_frame);
+                last_frame = now;
+
                 ctx.update();
                 let chain = ctx.chain(test_chain_id).unwrap();
                 //let mut paint_state = effect_node.new_paint_state(chain, &state.device);
@@ -224,6 +557,12 @@ fn main() {
                     name: Some("purple.glsl"),
                 };
 
+                // Pick up any shader edits made since the last frame.
+                #[cfg(not(target_arch = "wasm32"))]
+                for changed_path in shader_watcher.changed() {
+                    effect_node.notify_file_changed(&changed_path);
+                }
+
                 // Update and render effect node
                 effect_node.update(&ctx, &state.device, &state.queue, &args);
                 let (cmds, tex) = effect_node.paint(chain, &state.device, &mut paint_state);
@@ -238,14 +577,182 @@ fn main() {
                     purple_tex_id = Some(renderer.textures.insert(imgui_wgpu::Texture::from_radiance(tex.clone(), &state.device, &renderer)));
                 }
 
-                render_imgui(&window, &mut state, &mut imgui, &mut platform, &mut renderer, purple_tex_id.unwrap());
+                render_imgui(&window, &mut state, &mut imgui, &mut platform, &mut renderer, purple_tex_id.unwrap(), effect_node.last_error());
+
+                last_tex = Some(tex);
+            }
+            Event::RedrawRequested(window_id) if Some(window_id) == projector.as_ref().map(ProjectorWindow::id) => {
+                if let (Some(projector), Some(tex)) = (&mut projector, &last_tex) {
+                    projector.render(&state.device, &state.queue, tex);
+                }
             }
             Event::MainEventsCleared => {
                 // RedrawRequested will only trigger once, unless we manually
                 // request it.
                 window.request_redraw();
+                if let Some(projector) = &projector {
+                    projector.window.request_redraw();
+                }
             }
             _ => {}
         }
     });
 }
+
+/// The egui counterpart to `run()`: same wgpu setup and `DefaultContext` graph, but a
+/// dockable, retained-layout UI in place of the imgui immediate-mode window. Each graph node
+/// gets its own draggable `egui::Window` showing its live preview and an intensity slider.
+#[cfg(feature = "egui-ui")]
+async fn run_egui() {
+    let event_loop = EventLoop::new();
+    let window = WindowBuilder::new()
+        .build(&event_loop)
+        .unwrap();
+
+    let state: State = State::new(&window).await;
+
+    let egui_ctx = egui::Context::default();
+    let mut egui_winit_state = egui_winit::State::new(&event_loop);
+    let mut egui_renderer = egui_wgpu::Renderer::new(&state.device, state.sc_desc.format, None, 1);
+
+    let mut ctx = DefaultContext::new(&state.device, &state.queue);
+    let texture_size = 256;
+    let test_chain_id = ctx.add_chain(&state.device, &state.queue, (texture_size, texture_size));
+
+    // A small two-node chain (A feeds B's iChannel0) to give the node windows something to
+    // wire together; a real frontend would let the performer add and connect nodes from the UI.
+    let node_a = ctx.add_node();
+    let node_b = ctx.add_node();
+    ctx.node_mut(node_a).unwrap().set_name(Some("purple.glsl"));
+    ctx.node_mut(node_b).unwrap().set_name(Some("purple.glsl"));
+    ctx.connect(node_a, node_b, 0);
+
+    let chain = ctx.chain(test_chain_id).unwrap();
+    let mut paint_states: HashMap<GraphNodeId, radiance::EffectNodePaintState> = HashMap::new();
+    paint_states.insert(node_a, ctx.node(node_a).unwrap().new_paint_state(chain, &state.device));
+    paint_states.insert(node_b, ctx.node(node_b).unwrap().new_paint_state(chain, &state.device));
+
+    let mut egui_textures: HashMap<GraphNodeId, egui::TextureId> = HashMap::new();
+    let mut intensities: HashMap<GraphNodeId, f32> = HashMap::new();
+
+    let mut state = state;
+    event_loop.run(move |event, _, control_flow| {
+        match event {
+            Event::WindowEvent { ref event, window_id } if window_id == window.id() => {
+                let response = egui_winit_state.on_event(&egui_ctx, event);
+                if !response.consumed {
+                    match event {
+                        WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+                        WindowEvent::Resized(physical_size) => state.resize(*physical_size),
+                        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => state.resize(**new_inner_size),
+                        _ => {}
+                    }
+                }
+            }
+            Event::RedrawRequested(_) => {
+                ctx.update();
+                for (&id, intensity) in intensities.iter() {
+                    ctx.node_mut(id).unwrap().set_intensity(*intensity);
+                }
+
+                let (cmds, outputs) = ctx.update_and_paint_graph(&state.device, &state.queue, test_chain_id, &mut paint_states);
+                state.queue.submit(cmds);
+
+                // Register (or refresh) each node's preview texture, reusing its `TextureId`
+                // across frames the same way `render_imgui`'s `purple_tex_id` does.
+                for (&id, tex) in outputs.iter() {
+                    let view = tex.texture.create_view(&Default::default());
+                    match egui_textures.get(&id) {
+                        Some(&existing) => egui_renderer.update_egui_texture_from_wgpu_texture(&state.device, &view, wgpu::FilterMode::Linear, existing),
+                        None => {
+                            let id_egui = egui_renderer.register_native_texture(&state.device, &view, wgpu::FilterMode::Linear);
+                            egui_textures.insert(id, id_egui);
+                        }
+                    }
+                }
+
+                let raw_input = egui_winit_state.take_egui_input(&window);
+                let full_output = egui_ctx.run(raw_input, |egui_ctx| {
+                    for (i, &id) in [node_a, node_b].iter().enumerate() {
+                        egui::Window::new(format!("Node {}", i))
+                            .default_pos(egui::pos2(40. + i as f32 * 280., 40.))
+                            .show(egui_ctx, |ui| {
+                                if let Some(&tex_id) = egui_textures.get(&id) {
+                                    ui.image(tex_id, egui::vec2(200., 200.));
+                                }
+                                let intensity = intensities.entry(id).or_insert(0.);
+                                ui.add(egui::Slider::new(intensity, 0.0..=1.0).text("intensity"));
+                            });
+                    }
+                });
+
+                egui_winit_state.handle_platform_output(&window, &egui_ctx, full_output.platform_output);
+                let clipped_primitives = egui_ctx.tessellate(full_output.shapes);
+
+                let mut encoder = state.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("egui encoder"),
+                });
+                let screen_descriptor = egui_wgpu::renderer::ScreenDescriptor {
+                    size_in_pixels: [state.sc_desc.width, state.sc_desc.height],
+                    pixels_per_point: window.scale_factor() as f32,
+                };
+                for (texture_id, image_delta) in &full_output.textures_delta.set {
+                    egui_renderer.update_texture(&state.device, &state.queue, *texture_id, image_delta);
+                }
+                egui_renderer.update_buffers(&state.device, &state.queue, &mut encoder, &clipped_primitives, &screen_descriptor);
+
+                let output = match state.swap_chain.get_current_frame() {
+                    Ok(frame) => frame,
+                    Err(e) => {
+                        eprintln!("Error getting frame: {:?}", e);
+                        return;
+                    }
+                }
+                .output;
+
+                {
+                    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                        color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                            attachment: &output.view,
+                            resolve_target: None,
+                            ops: wgpu::Operations {
+                                load: wgpu::LoadOp::Clear(wgpu::Color { r: 0., g: 0., b: 0., a: 1. }),
+                                store: true,
+                            },
+                        }],
+                        depth_stencil_attachment: None,
+                    });
+                    egui_renderer.render(&mut pass, &clipped_primitives, &screen_descriptor);
+                }
+                for texture_id in &full_output.textures_delta.free {
+                    egui_renderer.free_texture(texture_id);
+                }
+
+                state.queue.submit(Some(encoder.finish()));
+            }
+            Event::MainEventsCleared => window.request_redraw(),
+            _ => {}
+        }
+    });
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {
+    #[cfg(feature = "egui-ui")]
+    futures::executor::block_on(run_egui());
+    #[cfg(not(feature = "egui-ui"))]
+    futures::executor::block_on(run());
+}
+
+// `#[wasm_bindgen(start)]` is the browser entry point: wasm-pack/wasm-bindgen call this
+// right after the module loads, in place of a native `main`.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen(start)]
+pub fn main() {
+    std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+    console_log::init_with_level(log::Level::Warn).expect("could not initialize logger");
+    // `run()` never returns (the event loop takes over for the lifetime of the page), so it's
+    // spawned as a local future rather than awaited: a browser tab can't block its one thread
+    // on it the way `block_on` does natively.
+    wasm_bindgen_futures::spawn_local(run());
+}
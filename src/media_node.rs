@@ -0,0 +1,318 @@
+use crate::types::{Texture, WorkerPool, FetchTexture, Timebase, WorkResult};
+use std::rc::Rc;
+use std::path::PathBuf;
+use std::fmt;
+use wgpu;
+
+/// Pending, settable configuration for a `MediaNode`, mirroring `EffectNode`'s
+/// `EffectNodePendingChanges`/`pending` pattern: changes are staged here and only take effect
+/// (triggering a re-decode) the next `update`.
+#[derive(Debug, Clone, Default)]
+pub struct MediaNodePendingChanges {
+    pub path: Option<PathBuf>,
+    // Advances one frame of a numbered sequence (`clip_0000.png`, `clip_0001.png`, ...) every
+    // this many seconds of `Timebase::time()`. `None` treats `path` as a single still image.
+    pub frame_period: Option<f32>,
+}
+
+/// One RGBA8 image decoded off the render thread, ready for `queue.write_texture` with no
+/// further CPU work. `width`/`height` ride along with the bytes since the decode (done inside
+/// the worker closure) is the only place that knows them.
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+/// Which planar YUV 4:2:0 layout a decoded video frame's chroma bytes use. NV12 interleaves U
+/// and V samples into one subsampled plane (what most hardware decoders emit); I420 keeps them
+/// as two separate subsampled planes, concatenated U-then-V.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum YuvFormat {
+    Nv12,
+    I420,
+}
+
+/// One decoded video frame's planar YUV 4:2:0 bytes, as handed back by a decode worker: `y` is
+/// `width * height` luma samples; `chroma` is the subsampled chroma bytes, laid out per
+/// `format` (NV12: `width/2 * height/2` interleaved UV pairs; I420: the U plane followed by the
+/// V plane, each `width/2 * height/2` bytes).
+pub struct YuvFrame {
+    pub width: u32,
+    pub height: u32,
+    pub format: YuvFormat,
+    pub y: Vec<u8>,
+    pub chroma: Vec<u8>,
+}
+
+/// The GPU-side textures for one uploaded `YuvFrame`. Kept as separate `y`/`chroma` textures
+/// (rather than color-converted to RGBA on the CPU) so an effect shader can sample both and do
+/// the YUV->RGB conversion itself, which is far cheaper done once per pixel on the GPU than
+/// once per frame on the CPU.
+pub struct YuvTextures {
+    pub y: Rc<Texture>,
+    pub chroma: Rc<Texture>,
+}
+
+/// Loads a still image (or a numbered frame sequence advanced by the shared clock) into a
+/// `wgpu::Texture`, wrapped in the same `Rc<Texture>` type `EffectNode::paint` returns so it
+/// can feed a graph's effects as an `iChannel` input the same way another node's output would.
+/// The decode itself runs on `UpdateContext`'s `WorkerPool` (mirroring `EffectNode`'s shader
+/// compilation), so a large image or a slow frame source never stalls the render thread.
+pub struct MediaNode<UpdateContext: WorkerPool + FetchTexture + Timebase> {
+    pending: MediaNodePendingChanges,
+    path: Option<PathBuf>,
+    frame_period: Option<f32>,
+    state: MediaNodeState<UpdateContext>,
+    // The frame index the current `state` was (or is being) decoded for, so `update()` can tell
+    // whether the clock has advanced to a new frame without inspecting `state` itself.
+    current_frame: Option<u32>,
+    // `time()` at the moment the current `path`/`frame_period` was adopted, so frame index is
+    // measured from when the clip started rather than from the shared clock's epoch.
+    started_at: f32,
+    last_error: Option<String>,
+}
+
+enum MediaNodeState<UpdateContext: WorkerPool + FetchTexture + Timebase> {
+    Uninitialized,
+    // Note: the work handle below is really not optional; see EffectNodeState::Compiling for
+    // why it's wrapped in an Option anyway (so it can be "taken" once decoding finishes).
+    Decoding {
+        decode_work_handle: Option<<UpdateContext as WorkerPool>::Handle<Result<DecodedImage, String>>>,
+        // The texture this decode is replacing, if any, so a failed re-decode (a corrupt frame
+        // partway through a sequence) keeps showing the last good frame instead of going blank.
+        fallback: Option<Rc<Texture>>,
+    },
+    Ready(Rc<Texture>),
+}
+
+impl<UpdateContext: WorkerPool + FetchTexture + Timebase> fmt::Debug for MediaNodeState<UpdateContext> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MediaNodeState::Uninitialized => write!(f, "Uninitialized"),
+            MediaNodeState::Decoding { .. } => write!(f, "Decoding"),
+            MediaNodeState::Ready(_) => write!(f, "Ready"),
+        }
+    }
+}
+
+impl<UpdateContext: WorkerPool + FetchTexture + Timebase> fmt::Debug for MediaNode<UpdateContext> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MediaNode")
+            .field("pending", &self.pending)
+            .field("path", &self.path)
+            .field("frame_period", &self.frame_period)
+            .field("state", &self.state)
+            .field("current_frame", &self.current_frame)
+            .field("started_at", &self.started_at)
+            .field("last_error", &self.last_error)
+            .finish()
+    }
+}
+
+impl<UpdateContext: WorkerPool + FetchTexture + Timebase> MediaNode<UpdateContext> {
+    pub fn new() -> Self {
+        MediaNode {
+            pending: MediaNodePendingChanges::default(),
+            path: None,
+            frame_period: None,
+            state: MediaNodeState::Uninitialized,
+            current_frame: None,
+            started_at: 0.,
+            last_error: None,
+        }
+    }
+
+    pub fn set_path(&mut self, path: Option<PathBuf>) {
+        self.pending.path = path;
+    }
+
+    pub fn set_frame_period(&mut self, frame_period: Option<f32>) {
+        self.pending.frame_period = frame_period;
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// Kicks off (or polls) a re-decode when the source path, frame period, or frame index has
+    /// changed since the last call; `format` should match the destination chain's texture
+    /// format (an sRGB format for the common 8-bit case, so the decoded bytes - already sRGB
+    /// encoded, straight out of the file - are interpreted correctly by samplers).
+    pub fn update(&mut self, context: &UpdateContext, device: &wgpu::Device, queue: &wgpu::Queue, format: wgpu::TextureFormat) {
+        let time = context.time();
+
+        if self.pending.path != self.path || self.pending.frame_period != self.frame_period {
+            self.path = self.pending.path.clone();
+            self.frame_period = self.pending.frame_period;
+            self.started_at = time;
+            self.current_frame = None;
+        }
+
+        if self.path.is_none() {
+            self.state = MediaNodeState::Uninitialized;
+            return;
+        }
+
+        let frame = match self.frame_period {
+            Some(period) if period > 0. => ((time - self.started_at) / period) as u32,
+            _ => 0,
+        };
+
+        let already_decoding = matches!(self.state, MediaNodeState::Decoding { .. });
+        if self.current_frame != Some(frame) && !already_decoding {
+            self.current_frame = Some(frame);
+            self.start_decoding(context, frame);
+        } else if let MediaNodeState::Decoding { decode_work_handle: handle_opt, .. } = &mut self.state {
+            let handle_ref = handle_opt.as_ref().unwrap();
+            let finished = !handle_ref.alive();
+            if finished {
+                let handle = handle_opt.take().unwrap();
+                let fallback = match std::mem::replace(&mut self.state, MediaNodeState::Uninitialized) {
+                    MediaNodeState::Decoding { fallback, .. } => fallback,
+                    _ => unreachable!(),
+                };
+                match handle.join() {
+                    WorkResult::Ok(Ok(decoded)) => {
+                        self.last_error = None;
+                        self.state = MediaNodeState::Ready(upload_rgba_texture(device, queue, &decoded, format));
+                    },
+                    WorkResult::Ok(Err(msg)) => {
+                        self.last_error = Some(msg);
+                        self.state = fallback.map(MediaNodeState::Ready).unwrap_or(MediaNodeState::Uninitialized);
+                    },
+                    WorkResult::Err(_) => {
+                        self.last_error = Some("Image decode panicked".to_owned());
+                        self.state = fallback.map(MediaNodeState::Ready).unwrap_or(MediaNodeState::Uninitialized);
+                    },
+                }
+            }
+        }
+    }
+
+    fn start_decoding(&mut self, context: &UpdateContext, frame: u32) {
+        let path = self.path.clone().unwrap();
+        let frame_path = if self.frame_period.is_some() {
+            numbered_frame_path(&path, frame)
+        } else {
+            path
+        };
+
+        let fetch = context.fetch_texture_closure(&frame_path.to_string_lossy());
+        let decode_work_handle = context.spawn(move || fetch());
+
+        // If we're mid-sequence, keep the currently showing texture as a fallback instead of
+        // dropping it, so a bad frame doesn't blank the node out while the next one decodes.
+        let fallback = match std::mem::replace(&mut self.state, MediaNodeState::Uninitialized) {
+            MediaNodeState::Ready(texture) => Some(texture),
+            MediaNodeState::Decoding { fallback, .. } => fallback,
+            MediaNodeState::Uninitialized => None,
+        };
+        self.state = MediaNodeState::Decoding { decode_work_handle: Some(decode_work_handle), fallback };
+    }
+
+    /// The currently displayed frame's texture, if one has finished decoding yet.
+    pub fn texture(&self) -> Option<Rc<Texture>> {
+        match &self.state {
+            MediaNodeState::Ready(texture) => Some(texture.clone()),
+            MediaNodeState::Decoding { fallback, .. } => fallback.clone(),
+            MediaNodeState::Uninitialized => None,
+        }
+    }
+}
+
+/// Renames `path`'s file stem to `{stem}_{frame:04}.{ext}`, e.g. `clip.png` at frame 7 becomes
+/// `clip_0007.png`.
+fn numbered_frame_path(path: &std::path::Path, frame: u32) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("frame");
+    let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("png");
+    let file_name = format!("{}_{:04}.{}", stem, frame, ext);
+    path.with_file_name(file_name)
+}
+
+/// How many bytes each pixel of `format` takes, for the single/dual/quad-channel 8-bit formats
+/// `upload_plane` is used with.
+fn bytes_per_pixel(format: wgpu::TextureFormat) -> u32 {
+    match format {
+        wgpu::TextureFormat::R8Unorm => 1,
+        wgpu::TextureFormat::Rg8Unorm => 2,
+        _ => 4,
+    }
+}
+
+/// Uploads a single tightly-packed 8-bit plane (RGBA, or one plane of a planar YUV frame) into
+/// a new `width`x`height` texture of `format`.
+fn upload_plane(device: &wgpu::Device, queue: &wgpu::Queue, data: &[u8], width: u32, height: u32, format: wgpu::TextureFormat, label: &str) -> Rc<Texture> {
+    let texture_size = wgpu::Extent3d {
+        width,
+        height,
+        depth: 1,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        size: texture_size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+        label: Some(label),
+    });
+
+    queue.write_texture(
+        wgpu::TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+        },
+        data,
+        wgpu::TextureDataLayout {
+            offset: 0,
+            bytes_per_row: bytes_per_pixel(format) * width,
+            rows_per_image: height,
+        },
+        texture_size,
+    );
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        ..Default::default()
+    });
+
+    Rc::new(Texture {
+        texture,
+        view,
+        sampler,
+    })
+}
+
+fn upload_rgba_texture(device: &wgpu::Device, queue: &wgpu::Queue, decoded: &DecodedImage, format: wgpu::TextureFormat) -> Rc<Texture> {
+    upload_plane(device, queue, &decoded.rgba, decoded.width, decoded.height, format, "media_node texture")
+}
+
+/// Uploads a decoded video frame's planar YUV bytes as two textures instead of color-converting
+/// to RGBA on the CPU first: the full-resolution Y plane as `R8Unorm`, and the chroma as either
+/// `Rg8Unorm` (NV12's interleaved UV, at half resolution on each axis) or `R8Unorm` (I420's
+/// separate U/V planes, stacked U-over-V into one half-width, full-height texture). An effect
+/// shader samples both and reconstructs RGB with the standard YUV->RGB matrix.
+pub fn upload_yuv_textures(device: &wgpu::Device, queue: &wgpu::Queue, frame: &YuvFrame) -> YuvTextures {
+    let y = upload_plane(device, queue, &frame.y, frame.width, frame.height, wgpu::TextureFormat::R8Unorm, "media_node y plane");
+
+    let chroma = match frame.format {
+        YuvFormat::Nv12 => upload_plane(
+            device, queue, &frame.chroma, frame.width / 2, frame.height / 2,
+            wgpu::TextureFormat::Rg8Unorm, "media_node chroma plane (nv12)",
+        ),
+        YuvFormat::I420 => upload_plane(
+            device, queue, &frame.chroma, frame.width / 2, frame.height,
+            wgpu::TextureFormat::R8Unorm, "media_node chroma plane (i420, u over v)",
+        ),
+    };
+
+    YuvTextures { y, chroma }
+}
@@ -0,0 +1,102 @@
+// A persistent on-disk cache for compiled shader artifacts, so reopening a project with many
+// `EffectNode`s doesn't recompile every one of them from source on every cold start. Artifacts
+// are keyed by a hash of exactly what determines their compiled bytes: the fully-preprocessed
+// (post `#include`/`#define`) source, plus the pass index and shader stage, since the same
+// source could in principle be handed to shaderc targeting a different stage or pass slot.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::fs;
+use std::path::PathBuf;
+
+/// Gated behind the `shader-cache` feature. `ShaderCache::new` always constructs successfully
+/// (so callers don't need to sprinkle `#[cfg]`s around every call site); with the feature
+/// disabled, `get` always misses and `put` is a no-op, so compilation behaves exactly as if no
+/// cache existed.
+#[derive(Debug, Clone)]
+pub struct ShaderCache {
+    dir: PathBuf,
+    enabled: bool,
+}
+
+impl ShaderCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let enabled = cfg!(feature = "shader-cache");
+        if enabled {
+            // Best-effort: if the cache directory can't be created, every `get`/`put` below
+            // just fails too, degrading to "no cache" rather than a hard error.
+            let _ = fs::create_dir_all(&dir);
+        }
+        ShaderCache { dir, enabled }
+    }
+
+    /// Hashes the inputs that fully determine a compiled artifact's bytes.
+    pub fn key(expanded_src: &str, pass_index: usize, shader_stage: &str) -> String {
+        let mut hasher = DefaultHasher::new();
+        expanded_src.hash(&mut hasher);
+        pass_index.hash(&mut hasher);
+        shader_stage.hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    }
+
+    fn path_for(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.spv", key))
+    }
+
+    /// Returns the cached artifact for `key`, if the feature is enabled and a prior `put` (in
+    /// this run or a previous one) wrote one.
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        if !self.enabled {
+            return None;
+        }
+        fs::read(self.path_for(key)).ok()
+    }
+
+    pub fn put(&self, key: &str, bytes: &[u8]) {
+        if !self.enabled {
+            return;
+        }
+        // Best-effort for the same reason as `new`: a cache write failing shouldn't fail the
+        // compile that already succeeded.
+        let _ = fs::write(self.path_for(key), bytes);
+    }
+
+    /// Deletes every cached artifact, for invalidating the cache across a naga/shaderc version
+    /// bump or any other change that would make previously-cached bytes unsafe to reuse.
+    pub fn clear_cache(&self) -> std::io::Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+        if self.enabled {
+            fs::create_dir_all(&self.dir)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_is_deterministic_and_sensitive_to_inputs() {
+        let key = ShaderCache::key("void main() {}", 0, "fragment");
+        assert_eq!(key, ShaderCache::key("void main() {}", 0, "fragment"));
+        assert_ne!(key, ShaderCache::key("void main() {}", 1, "fragment"));
+        assert_ne!(key, ShaderCache::key("void main() {}", 0, "compute"));
+        assert_ne!(key, ShaderCache::key("void main() {}\n", 0, "fragment"));
+    }
+
+    #[test]
+    fn test_disabled_cache_always_misses() {
+        let dir = std::env::temp_dir().join("radiance_shader_cache_test_disabled");
+        let cache = ShaderCache::new(&dir);
+        cache.put("some_key", &[1, 2, 3]);
+        // With the `shader-cache` feature off (the default in these tests), this should never
+        // have actually written anything.
+        if !cfg!(feature = "shader-cache") {
+            assert_eq!(cache.get("some_key"), None);
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::{HashMap, HashSet};
+
+/// Flattens a tree of GLSL shader sources into a single string suitable for handing to shaderc,
+/// resolving `#include "path"` and `#define NAME value` so common helpers (noise, color
+/// conversions, UV remapping, ...) can live in a shared library instead of being copy-pasted
+/// into every `EffectNode` shader.
+///
+/// `fetch` loads the source for an include path (relative to whatever search directory the
+/// caller configured it with); `EffectNode` backs this with `FetchContent::fetch_content_closure`
+/// so includes are resolved through the same content-loading path as the top-level shader.
+///
+/// Already-included paths act as include guards (a file included from two different branches of
+/// the tree is only spliced in once), and the current include chain is tracked to detect cycles
+/// and report them as an error instead of recursing forever.
+pub fn preprocess<F>(root_path: &str, root_src: &str, mut fetch: F) -> Result<String, String>
+where
+    F: FnMut(&str) -> Result<String, String>,
+{
+    let mut defines: HashMap<String, String> = HashMap::new();
+    let mut included: HashSet<String> = HashSet::new();
+    let mut chain: Vec<String> = Vec::new();
+    let mut out = String::new();
+    expand(root_path, root_src, &mut fetch, &mut defines, &mut included, &mut chain, &mut out)?;
+    Ok(out)
+}
+
+fn expand<F>(
+    path: &str,
+    src: &str,
+    fetch: &mut F,
+    defines: &mut HashMap<String, String>,
+    included: &mut HashSet<String>,
+    chain: &mut Vec<String>,
+    out: &mut String,
+) -> Result<(), String>
+where
+    F: FnMut(&str) -> Result<String, String>,
+{
+    if chain.iter().any(|p| p == path) {
+        chain.push(path.to_owned());
+        return Err(format!("include cycle detected: {}", chain.join(" -> ")));
+    }
+    chain.push(path.to_owned());
+
+    // Emit a `#line` directive so naga/shaderc compile errors in the flattened output
+    // still point back at the line they actually came from in the original file.
+    out.push_str(&format!("#line 1 \"{}\"\n", path));
+
+    for (line_no, line) in src.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if let Some(rest) = trimmed.strip_prefix("#include") {
+            let include_path = parse_quoted(rest.trim())
+                .ok_or_else(|| format!("{}:{}: malformed #include, expected #include \"path\"", path, line_no + 1))?;
+
+            // Include guard: a file already spliced in anywhere in the tree is skipped silently,
+            // exactly like a C header guard.
+            if included.insert(include_path.clone()) {
+                let include_src = fetch(&include_path)
+                    .map_err(|e| format!("{}:{}: failed to include \"{}\": {}", path, line_no + 1, include_path, e))?;
+                expand(&include_path, &include_src, fetch, defines, included, chain, out)?;
+                out.push_str(&format!("#line {} \"{}\"\n", line_no + 2, path));
+            }
+        } else if let Some(rest) = trimmed.strip_prefix("#define") {
+            let mut parts = rest.trim().splitn(2, char::is_whitespace);
+            let name = parts.next()
+                .ok_or_else(|| format!("{}:{}: malformed #define, expected #define NAME value", path, line_no + 1))?
+                .to_owned();
+            let value = parts.next().unwrap_or("").trim().to_owned();
+            defines.insert(name, value);
+        } else {
+            out.push_str(&substitute_defines(line, defines));
+            out.push('\n');
+        }
+    }
+
+    chain.pop();
+    Ok(())
+}
+
+/// Extracts the contents of a `"..."`-quoted path, e.g. `"lib.wgsl"` -> `lib.wgsl`.
+fn parse_quoted(s: &str) -> Option<String> {
+    let s = s.strip_prefix('"')?;
+    let s = s.strip_suffix('"')?;
+    Some(s.to_owned())
+}
+
+/// Performs simple whole-word token substitution of any `#define`d names found in `line`.
+fn substitute_defines(line: &str, defines: &HashMap<String, String>) -> String {
+    if defines.is_empty() {
+        return line.to_owned();
+    }
+    let mut out = String::with_capacity(line.len());
+    let mut token = String::new();
+    let flush = |token: &mut String, out: &mut String| {
+        if !token.is_empty() {
+            match defines.get(token.as_str()) {
+                Some(value) => out.push_str(value),
+                None => out.push_str(token),
+            }
+            token.clear();
+        }
+    };
+    for c in line.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            token.push(c);
+        } else {
+            flush(&mut token, &mut out);
+            out.push(c);
+        }
+    }
+    flush(&mut token, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fetch_from(files: HashMap<&'static str, &'static str>) -> impl FnMut(&str) -> Result<String, String> {
+        move |path| {
+            files.get(path)
+                .map(|s| s.to_string())
+                .ok_or_else(|| format!("no such file: {}", path))
+        }
+    }
+
+    #[test]
+    fn test_simple_include() {
+        let mut files = HashMap::new();
+        files.insert("lib.glsl", "float helper() { return 1.0; }");
+        let out = preprocess(
+            "main.glsl",
+            "#include \"lib.glsl\"\nvoid main() {}",
+            fetch_from(files),
+        ).unwrap();
+        assert!(out.contains("float helper() { return 1.0; }"));
+        assert!(out.contains("void main() {}"));
+    }
+
+    #[test]
+    fn test_include_cycle_errors_instead_of_recursing() {
+        let mut files = HashMap::new();
+        files.insert("a.glsl", "#include \"b.glsl\"");
+        files.insert("b.glsl", "#include \"a.glsl\"");
+        let err = preprocess("a.glsl", files["a.glsl"], fetch_from(files.clone())).unwrap_err();
+        assert!(err.contains("include cycle detected"));
+        assert!(err.contains("a.glsl"));
+        assert!(err.contains("b.glsl"));
+    }
+
+    #[test]
+    fn test_include_guard_splices_shared_file_only_once() {
+        let mut files = HashMap::new();
+        files.insert("shared.glsl", "float shared_helper() { return 1.0; }");
+        files.insert("a.glsl", "#include \"shared.glsl\"");
+        files.insert("b.glsl", "#include \"shared.glsl\"");
+        let out = preprocess(
+            "main.glsl",
+            "#include \"a.glsl\"\n#include \"b.glsl\"\nvoid main() {}",
+            fetch_from(files),
+        ).unwrap();
+        assert_eq!(out.matches("float shared_helper() { return 1.0; }").count(), 1);
+    }
+
+    #[test]
+    fn test_define_substitution() {
+        let out = preprocess(
+            "main.glsl",
+            "#define SCALE 2.0\nfloat x = SCALE * SCALE;",
+            fetch_from(HashMap::new()),
+        ).unwrap();
+        assert!(out.contains("float x = 2.0 * 2.0;"));
+        assert!(!out.contains("SCALE"));
+    }
+}
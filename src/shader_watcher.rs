@@ -0,0 +1,57 @@
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::mpsc;
+
+/// Watches a directory of effect shaders on disk and reports which files have changed,
+/// so a live `EffectNode` can be recompiled in place on the next frame instead of requiring
+/// a restart of the whole app to pick up an edit.
+///
+/// Mirrors the producer/consumer channel pattern `Mir` uses to hand audio-thread results
+/// back to the main thread: `notify`'s background thread is the producer, and `changed()`
+/// drains whatever's piled up since the last call on the main thread.
+pub struct ShaderWatcher {
+    // Kept alive for as long as the watch should run; dropping it stops the watch.
+    _watcher: RecommendedWatcher,
+    receiver: mpsc::Receiver<String>,
+}
+
+impl ShaderWatcher {
+    // How many pending file-change notifications we're willing to queue up
+    // before the main thread has had a chance to drain them.
+    const CHANGE_BUFFER_SIZE: usize = 256;
+
+    /// Begins watching `dir` (and its subdirectories) for shader file changes.
+    pub fn new(dir: impl AsRef<Path>) -> notify::Result<Self> {
+        let (sender, receiver) = mpsc::sync_channel(Self::CHANGE_BUFFER_SIZE);
+
+        let mut watcher = RecommendedWatcher::new(move |res: notify::Result<notify::Event>| {
+            let event = match res {
+                Ok(event) => event,
+                Err(err) => {
+                    println!("ShaderWatcher: watch error: {:?}", err);
+                    return;
+                },
+            };
+            for path in event.paths {
+                if let Some(path) = path.to_str() {
+                    // Never block the watcher thread waiting on the main thread;
+                    // a dropped notification means that edit goes unnoticed until the
+                    // file changes again.
+                    let _ = sender.try_send(path.to_owned());
+                }
+            }
+        }, notify::Config::default())?;
+
+        watcher.watch(dir.as_ref(), RecursiveMode::Recursive)?;
+
+        Ok(ShaderWatcher {
+            _watcher: watcher,
+            receiver,
+        })
+    }
+
+    /// Drains and returns the paths that have changed since the last call.
+    pub fn changed(&self) -> Vec<String> {
+        self.receiver.try_iter().collect()
+    }
+}
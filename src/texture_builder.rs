@@ -0,0 +1,266 @@
+// A shared builder for the small number of textures the context layer creates directly (the
+// 1x1 blank fallback texture, each `DefaultChain`'s noise texture): factors out the
+// create-texture/write-texture/view/sampler boilerplate that `create_blank_texture` and
+// `DefaultChain::new` used to duplicate, and optionally allocates a full mip chain with every
+// level past the base generated on the GPU (one full-screen blit pass per level, sampling the
+// previous level through a linear filter), rather than uploading a single level and leaving a
+// minified sampling of it to alias.
+
+use crate::types::{GraphicsContext, Texture};
+use std::num::NonZeroU32;
+use std::rc::Rc;
+use wgpu;
+
+/// What a built texture should contain and look like. Every texture this builder constructs
+/// is `Rgba8UnormSrgb`, the only format `create_blank_texture`/`DefaultChain::new` have ever
+/// needed.
+pub struct TextureOptions<'a> {
+    pub size: (u32, u32),
+    /// Allocates `mip_level_count(size)` levels and fills levels past the base via GPU blits,
+    /// instead of the single level a `false` here allocates.
+    pub mipmapped: bool,
+    /// Used for both `mag_filter` and `min_filter` on the returned texture's sampler; mip
+    /// levels (when `mipmapped`) are always sampled `Linear` regardless of this.
+    pub filter: wgpu::FilterMode,
+    pub label: &'a str,
+}
+
+/// `floor(log2(max(w, h))) + 1`: the number of mip levels needed to shrink a `w`x`h` texture
+/// all the way down to its 1x1 level. `pub(crate)` rather than private: `preview_mips` needs
+/// the same arithmetic to size its own mip chain to match.
+pub(crate) fn mip_level_count(size: (u32, u32)) -> u32 {
+    32 - size.0.max(size.1).max(1).leading_zeros()
+}
+
+/// Builds a texture per `options`, uploads `initial_bytes` (one `Rgba8UnormSrgb` quadruple per
+/// pixel of `options.size`) into its base level, and, if `options.mipmapped`, generates the
+/// rest of its mip chain on the GPU.
+pub fn build_texture(graphics: &GraphicsContext, options: TextureOptions, initial_bytes: &[u8]) -> Rc<Texture> {
+    let mip_level_count = if options.mipmapped { mip_level_count(options.size) } else { 1 };
+
+    let texture_size = wgpu::Extent3d {
+        width: options.size.0,
+        height: options.size.1,
+        depth: 1,
+    };
+
+    let mut usage = wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST;
+    if options.mipmapped {
+        // Each mip level past the base is a render target for the blit pass that fills it.
+        usage |= wgpu::TextureUsage::RENDER_ATTACHMENT;
+    }
+
+    let texture = graphics.device.create_texture(
+        &wgpu::TextureDescriptor {
+            size: texture_size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage,
+            label: Some(options.label),
+        }
+    );
+
+    graphics.queue.write_texture(
+        wgpu::TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+        },
+        initial_bytes,
+        wgpu::TextureDataLayout {
+            offset: 0,
+            bytes_per_row: 4 * options.size.0,
+            rows_per_image: options.size.1,
+        },
+        texture_size,
+    );
+
+    if mip_level_count > 1 {
+        generate_mips(graphics, &texture, mip_level_count);
+    }
+
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let sampler = graphics.device.create_sampler(
+        &wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: options.filter,
+            min_filter: options.filter,
+            mipmap_filter: if mip_level_count > 1 { wgpu::FilterMode::Linear } else { wgpu::FilterMode::Nearest },
+            ..Default::default()
+        }
+    );
+
+    Rc::new(Texture {
+        texture,
+        view,
+        sampler,
+    })
+}
+
+// Reuses the same precompiled fullscreen-triangle vertex stage (`effect_vertex.spv`) every
+// `EffectNode` render pass already draws with, paired with the same minimal "sample this
+// texture" fragment shader `main.rs`'s projector blit pass uses, so this doesn't need its own
+// second copy of either shader.
+const BLIT_FRAGMENT_SHADER_SOURCE: &str = "
+#version 450
+layout(location = 0) in vec2 uv;
+layout(location = 0) out vec4 outColor;
+layout(set = 0, binding = 0) uniform texture2D iTex;
+layout(set = 0, binding = 1) uniform sampler iSampler;
+void main() {
+    outColor = texture(sampler2D(iTex, iSampler), uv);
+}
+";
+
+/// Fills mip levels `1..mip_level_count` of `texture` by blitting each level from the one
+/// below it through a linear filter, one fullscreen triangle-strip draw per level.
+fn generate_mips(graphics: &GraphicsContext, texture: &wgpu::Texture, mip_level_count: u32) {
+    let device = &graphics.device;
+
+    let vs_module = device.create_shader_module(wgpu::include_spirv!(concat!(env!("OUT_DIR"), "/effect_vertex.spv")));
+    let mut compiler = shaderc::Compiler::new().unwrap();
+    let fs_binary = compiler
+        .compile_into_spirv(BLIT_FRAGMENT_SHADER_SOURCE, shaderc::ShaderKind::Fragment, "mip blit", "main", None)
+        .expect("failed to compile the built-in mip-generation blit shader");
+    let fs_module = device.create_shader_module(wgpu::util::make_spirv(fs_binary.as_binary_u8()));
+
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::SampledTexture {
+                    multisampled: false,
+                    dimension: wgpu::TextureViewDimension::D2,
+                    component_type: wgpu::TextureComponentType::Uint,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStage::FRAGMENT,
+                ty: wgpu::BindingType::Sampler { comparison: false },
+                count: None,
+            },
+        ],
+        label: Some("mip blit bind group layout"),
+    });
+
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Mip Blit Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Mip Blit Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex_stage: wgpu::ProgrammableStageDescriptor {
+            module: &vs_module,
+            entry_point: "main",
+        },
+        fragment_stage: Some(wgpu::ProgrammableStageDescriptor {
+            module: &fs_module,
+            entry_point: "main",
+        }),
+        rasterization_state: Some(wgpu::RasterizationStateDescriptor {
+            front_face: wgpu::FrontFace::Cw,
+            cull_mode: wgpu::CullMode::Back,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+            clamp_depth: false,
+        }),
+        color_states: &[wgpu::ColorStateDescriptor {
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            color_blend: wgpu::BlendDescriptor::REPLACE,
+            alpha_blend: wgpu::BlendDescriptor::REPLACE,
+            write_mask: wgpu::ColorWrite::ALL,
+        }],
+        primitive_topology: wgpu::PrimitiveTopology::TriangleStrip,
+        depth_stencil_state: None,
+        vertex_state: wgpu::VertexStateDescriptor {
+            index_format: wgpu::IndexFormat::Uint16,
+            vertex_buffers: &[],
+        },
+        sample_count: 1,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    });
+
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+        address_mode_u: wgpu::AddressMode::ClampToEdge,
+        address_mode_v: wgpu::AddressMode::ClampToEdge,
+        address_mode_w: wgpu::AddressMode::ClampToEdge,
+        mag_filter: wgpu::FilterMode::Linear,
+        min_filter: wgpu::FilterMode::Linear,
+        mipmap_filter: wgpu::FilterMode::Nearest,
+        ..Default::default()
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("mip generation encoder"),
+    });
+
+    for level in 1..mip_level_count {
+        let src_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            base_mip_level: level - 1,
+            level_count: NonZeroU32::new(1),
+            ..Default::default()
+        });
+        let dst_view = texture.create_view(&wgpu::TextureViewDescriptor {
+            base_mip_level: level,
+            level_count: NonZeroU32::new(1),
+            ..Default::default()
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&src_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("mip blit bind group"),
+        });
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            color_attachments: &[wgpu::RenderPassColorAttachmentDescriptor {
+                attachment: &dst_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        pass.set_pipeline(&pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.draw(0..4, 0..1);
+    }
+
+    graphics.queue.submit(Some(encoder.finish()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mip_level_count() {
+        assert_eq!(mip_level_count((1, 1)), 1);
+        assert_eq!(mip_level_count((2, 2)), 2);
+        assert_eq!(mip_level_count((256, 256)), 9);
+        assert_eq!(mip_level_count((300, 100)), 9); // floor(log2(300)) + 1
+    }
+}
@@ -0,0 +1,55 @@
+use wgpu;
+
+/// A bump allocator over one shared `wgpu::Buffer`, used to sub-allocate the small, fixed-size
+/// uniform structs (`UpdateUniforms`, `PaintUniforms`, ...) that used to each get their own
+/// dedicated buffer per `EffectNode`. Every allocation is rounded up to the device's
+/// `min_uniform_buffer_offset_alignment` (typically 256 bytes), so the returned offset is
+/// always valid to pass as a dynamic offset to `set_bind_group`. Allocations are permanent:
+/// nothing is ever freed, since the node that holds an offset lives for as long as the scene
+/// that created it.
+#[derive(Debug)]
+pub struct UniformArena {
+    buffer: wgpu::Buffer,
+    alignment: wgpu::BufferAddress,
+    capacity: wgpu::BufferAddress,
+    cursor: wgpu::BufferAddress,
+}
+
+impl UniformArena {
+    /// `capacity` is the arena's total size in bytes, fixed for its whole lifetime: growing it
+    /// would mean reallocating the buffer out from under every bind group that already points
+    /// at it, so callers are expected to size it generously for the scene up front instead.
+    pub fn new(device: &wgpu::Device, label: &str, capacity: wgpu::BufferAddress) -> UniformArena {
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: capacity,
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        UniformArena {
+            buffer,
+            alignment: device.limits().min_uniform_buffer_offset_alignment as wgpu::BufferAddress,
+            capacity,
+            cursor: 0,
+        }
+    }
+
+    /// Reserves `size` bytes (rounded up to the arena's alignment) and returns the byte offset
+    /// to write into and bind at. Panics if the arena's fixed `capacity` is exhausted.
+    pub fn allocate(&mut self, size: wgpu::BufferAddress) -> wgpu::BufferAddress {
+        let offset = self.cursor;
+        let slot = ((size + self.alignment - 1) / self.alignment) * self.alignment;
+        assert!(offset + slot <= self.capacity, "UniformArena exhausted: increase its capacity");
+        self.cursor += slot;
+        offset
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    pub fn write(&self, queue: &wgpu::Queue, offset: wgpu::BufferAddress, bytes: &[u8]) {
+        queue.write_buffer(&self.buffer, offset, bytes);
+    }
+}